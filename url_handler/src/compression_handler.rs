@@ -0,0 +1,102 @@
+use anyhow::Result;
+use std::io::{Read, Write};
+
+pub trait CompressionHandler {
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>>;
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum KnownCompressionHandler {
+    Gzip(GzipHandler),
+    Zstd(ZstdHandler),
+    Bzip2(Bzip2Handler),
+    Xz(XzHandler),
+}
+
+impl KnownCompressionHandler {
+    pub fn to_handler(&self) -> &dyn CompressionHandler {
+        match self {
+            KnownCompressionHandler::Gzip(handler) => handler as &dyn CompressionHandler,
+            KnownCompressionHandler::Zstd(handler) => handler as &dyn CompressionHandler,
+            KnownCompressionHandler::Bzip2(handler) => handler as &dyn CompressionHandler,
+            KnownCompressionHandler::Xz(handler) => handler as &dyn CompressionHandler,
+        }
+    }
+}
+
+impl CompressionHandler for KnownCompressionHandler {
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        self.to_handler().compress(bytes)
+    }
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        self.to_handler().decompress(bytes)
+    }
+}
+
+pub fn get_compression_handler_for_extension(extension: &str) -> Option<KnownCompressionHandler> {
+    let handler = match extension {
+        "gz" | "gzip" => KnownCompressionHandler::Gzip(GzipHandler::default()),
+        "zst" | "zstd" => KnownCompressionHandler::Zstd(ZstdHandler::default()),
+        "bz2" | "bzip2" => KnownCompressionHandler::Bzip2(Bzip2Handler::default()),
+        "xz" => KnownCompressionHandler::Xz(XzHandler::default()),
+        _ => return None,
+    };
+    Some(handler)
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct GzipHandler {}
+impl CompressionHandler for GzipHandler {
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(bytes)?;
+        Ok(encoder.finish()?)
+    }
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct ZstdHandler {}
+impl CompressionHandler for ZstdHandler {
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        Ok(zstd::encode_all(bytes, zstd::DEFAULT_COMPRESSION_LEVEL)?)
+    }
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        Ok(zstd::decode_all(bytes)?)
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct Bzip2Handler {}
+impl CompressionHandler for Bzip2Handler {
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(bytes)?;
+        Ok(encoder.finish()?)
+    }
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        bzip2::read::BzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct XzHandler {}
+impl CompressionHandler for XzHandler {
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(bytes)?;
+        Ok(encoder.finish()?)
+    }
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        xz2::read::XzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+}
@@ -0,0 +1,107 @@
+use age::{secrecy::SecretString, Decryptor, Encryptor, Identity, IdentityFile, Recipient};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::iter;
+use std::path::PathBuf;
+use std::str::FromStr;
+use url::Url;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EncryptionHandlerConfig {
+    recipients: Option<Vec<String>>,
+    identities: Option<Vec<String>>,
+    identity_files: Option<Vec<PathBuf>>,
+    passphrase: Option<String>,
+    work_factor: Option<u8>,
+    prefixes: Option<Vec<String>>,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct EncryptionHandler {
+    config: EncryptionHandlerConfig,
+}
+
+impl EncryptionHandler {
+    pub fn new(config: &EncryptionHandlerConfig) -> Self {
+        EncryptionHandler {
+            config: config.clone(),
+        }
+    }
+
+    pub fn is_configured_for_url(&self, url: &Url) -> bool {
+        match &self.config.prefixes {
+            None => false,
+            Some(prefixes) => prefixes
+                .iter()
+                .any(|prefix| url.as_str().starts_with(prefix.as_str())),
+        }
+    }
+
+    pub fn encrypt(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let encryptor = match (&self.config.recipients, &self.config.passphrase) {
+            (Some(recipients), _) if !recipients.is_empty() => {
+                let mut parsed: Vec<age::x25519::Recipient> = Vec::new();
+                for recipient in recipients {
+                    match age::x25519::Recipient::from_str(recipient) {
+                        Ok(recipient) => parsed.push(recipient),
+                        Err(error) => anyhow::bail!("Could not parse recipient due to {error}"),
+                    }
+                }
+                Encryptor::with_recipients(parsed.iter().map(|recipient| recipient as _))?
+            }
+            (_, Some(passphrase)) => {
+                let mut recipient =
+                    age::scrypt::Recipient::new(SecretString::from(passphrase.clone()));
+                if let Some(work_factor) = self.config.work_factor {
+                    recipient.set_work_factor(work_factor);
+                }
+                Encryptor::with_recipients(iter::once(&recipient as &dyn Recipient))?
+            }
+            _ => anyhow::bail!("Can not encrypt without configured recipients or passphrase!"),
+        };
+
+        let mut encrypted = Vec::new();
+        let mut writer = encryptor.wrap_output(&mut encrypted)?;
+        writer.write_all(bytes)?;
+        writer.finish()?;
+        Ok(encrypted)
+    }
+
+    pub fn decrypt(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let decryptor = Decryptor::new(bytes)?;
+        let identities = self.build_identities(decryptor.is_scrypt())?;
+        if identities.is_empty() {
+            anyhow::bail!("Can not decrypt without configured identities or passphrase!");
+        }
+
+        let mut decrypted = Vec::new();
+        decryptor
+            .decrypt(identities.iter().map(|identity| identity.as_ref()))?
+            .read_to_end(&mut decrypted)?;
+        Ok(decrypted)
+    }
+
+    fn build_identities(&self, is_scrypt: bool) -> Result<Vec<Box<dyn Identity>>> {
+        let mut identities: Vec<Box<dyn Identity>> = Vec::new();
+        if is_scrypt {
+            if let Some(passphrase) = &self.config.passphrase {
+                let identity = age::scrypt::Identity::new(SecretString::from(passphrase.clone()));
+                identities.push(Box::new(identity));
+            }
+            return Ok(identities);
+        }
+
+        for identity in self.config.identities.iter().flatten() {
+            match age::x25519::Identity::from_str(identity) {
+                Ok(identity) => identities.push(Box::new(identity)),
+                Err(error) => anyhow::bail!("Could not parse identity due to {error}"),
+            }
+        }
+        for path in self.config.identity_files.iter().flatten() {
+            let file = IdentityFile::from_file(path.to_string_lossy().to_string())?;
+            identities.extend(file.into_identities()?);
+        }
+        Ok(identities)
+    }
+}
@@ -1,2 +1,6 @@
+mod command;
+pub use command::CommandFascade;
+mod git;
+pub use git::{GitAuthor, GitFascade};
 mod openssh;
 pub use openssh::OpenSSHFascade;
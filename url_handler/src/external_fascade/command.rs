@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub struct CommandFascade {}
+
+impl CommandFascade {
+    pub fn run(command_line: &str, stdin: Option<&str>, timeout: Duration) -> Result<String> {
+        let arguments = shellwords::split(command_line)?;
+        let Some((program, arguments)) = arguments.split_first() else {
+            anyhow::bail!("Can not run an empty command!");
+        };
+
+        let mut child = Command::new(program)
+            .args(arguments)
+            .stdin(match stdin {
+                Some(_) => Stdio::piped(),
+                None => Stdio::null(),
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Could not spawn '{program}'"))?;
+
+        let stdin_writer = match (stdin, child.stdin.take()) {
+            (Some(string), Some(mut pipe)) => {
+                let string = string.to_string();
+                Some(thread::spawn(move || pipe.write_all(string.as_bytes())))
+            }
+            _ => None,
+        };
+        let mut stdout_pipe = child.stdout.take().context("Could not capture stdout")?;
+        let stdout_reader = thread::spawn(move || {
+            let mut buffer = Vec::new();
+            stdout_pipe.read_to_end(&mut buffer).map(|_| buffer)
+        });
+        let mut stderr_pipe = child.stderr.take().context("Could not capture stderr")?;
+        let stderr_reader = thread::spawn(move || {
+            let mut buffer = Vec::new();
+            stderr_pipe.read_to_end(&mut buffer).map(|_| buffer)
+        });
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                child.kill()?;
+                child.wait()?;
+                anyhow::bail!(
+                    "Command '{program}' did not finish within {}",
+                    humantime::format_duration(timeout)
+                );
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        if let Some(writer) = stdin_writer {
+            // The command is allowed to exit without consuming all of its input
+            let _ = writer.join();
+        }
+        let stdout = stdout_reader
+            .join()
+            .map_err(|_| anyhow::anyhow!("Could not read stdout of '{program}'"))??;
+        let stderr = stderr_reader
+            .join()
+            .map_err(|_| anyhow::anyhow!("Could not read stderr of '{program}'"))??;
+
+        if !status.success() {
+            anyhow::bail!(
+                "Command '{program}' failed with {status}: {}",
+                String::from_utf8_lossy(&stderr).trim()
+            );
+        }
+        Ok(String::from_utf8(stdout)?)
+    }
+}
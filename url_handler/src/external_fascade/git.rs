@@ -0,0 +1,101 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub struct GitFascade {}
+
+pub struct GitAuthor<'a> {
+    pub name: &'a str,
+    pub email: &'a str,
+}
+
+impl GitFascade {
+    fn run(repository: &Path, arguments: &[&str], author: Option<&GitAuthor>) -> Result<Vec<u8>> {
+        let mut git = Command::new("git");
+        git.arg("-C").arg(repository);
+        if let Some(author) = author {
+            git.arg("-c")
+                .arg(format!("user.name={}", author.name))
+                .arg("-c")
+                .arg(format!("user.email={}", author.email));
+        }
+        let output = git
+            .args(arguments)
+            .output()
+            .context("Could not spawn 'git'")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed with {}: {}",
+                arguments.first().unwrap_or(&""),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(output.stdout)
+    }
+
+    pub fn get_toplevel(path: &Path) -> Result<PathBuf> {
+        let directory = path
+            .ancestors()
+            .find(|ancestor| ancestor.is_dir())
+            .context("Could not find an existing directory for the repository")?;
+        let stdout = Self::run(directory, &["rev-parse", "--show-toplevel"], None)?;
+        Ok(PathBuf::from(String::from_utf8(stdout)?.trim_end()))
+    }
+
+    pub fn show(repository: &Path, revision: &str, path: &str) -> Result<Vec<u8>> {
+        Self::run(repository, &["show", &format!("{revision}:{path}")], None)
+    }
+
+    pub fn list_tree(repository: &Path, revision: &str, path: &str) -> Result<Vec<(String, bool)>> {
+        let path = match path.trim_end_matches('/') {
+            "" => String::new(),
+            path => format!("{path}/"),
+        };
+        let mut arguments = vec!["ls-tree", "-z", revision];
+        if !path.is_empty() {
+            arguments.push(&path);
+        }
+        let stdout = String::from_utf8(Self::run(repository, &arguments, None)?)?;
+
+        let mut entries = Vec::new();
+        for entry in stdout.split('\0').filter(|entry| !entry.is_empty()) {
+            let Some((info, name)) = entry.split_once('\t') else {
+                anyhow::bail!("Could not parse tree entry '{entry}'");
+            };
+            entries.push((name.to_string(), info.split(' ').nth(1) == Some("tree")));
+        }
+        Ok(entries)
+    }
+
+    // Returns false if there was nothing to commit for the path.
+    pub fn commit_path(
+        repository: &Path,
+        path: &str,
+        message: &str,
+        author: Option<&GitAuthor>,
+    ) -> Result<bool> {
+        Self::run(repository, &["add", "--all", "--", path], None)?;
+        let has_changes = Command::new("git")
+            .arg("-C")
+            .arg(repository)
+            .args(["diff", "--cached", "--quiet", "--", path])
+            .status()?
+            .code()
+            == Some(1);
+        if !has_changes {
+            return Ok(false);
+        }
+        Self::run(repository, &["commit", "-m", message, "--", path], author)?;
+        Ok(true)
+    }
+
+    pub fn push(repository: &Path, remote: &str, branch: Option<&str>) -> Result<()> {
+        let refspec = match branch {
+            Some(branch) => format!("HEAD:{branch}"),
+            None => "HEAD".to_string(),
+        };
+        Self::run(repository, &["push", remote, &refspec], None)?;
+        Ok(())
+    }
+}
@@ -1,11 +1,72 @@
+use crate::protocol_handler::{UrlMetadata, UrlVersion};
 use anyhow::Result;
+use chrono::DateTime;
+use percent_encoding::percent_decode_str;
 use std::ffi::OsStr;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Output};
+use std::thread;
+use std::time::{Duration, Instant};
 use url::Url;
 
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct OpenSSHFascade {}
 
+pub struct RemoteLock {
+    url: Url,
+    lock_path: String,
+}
+
+impl Drop for RemoteLock {
+    fn drop(&mut self) {
+        let command = format!("rmdir -- {}", shellwords::escape(&self.lock_path));
+        if let Err(error) = OpenSSHFascade::run_remote_command(&self.url, &command) {
+            log::warn!("Could not release lock '{}': {error}", self.lock_path);
+        }
+    }
+}
+
+// GNU systems ship `sha256sum`, BSD and macOS `shasum`, both print the hash first.
+fn build_hash_command(path: &str) -> String {
+    format!("{{ sha256sum {path} 2>/dev/null || shasum -a 256 {path}; }}")
+}
+
+// The resolved path is compared on the remote, as only it knows its root and home.
+fn build_container_guard(path: &str, recursive: bool) -> String {
+    let empty = match recursive {
+        true => String::new(),
+        false => format!(
+            "; [ -z \"$(ls -A -- {path})\" ] || {{ echo 'not empty' >&2; exit 1; }}"
+        ),
+    };
+    format!(
+        "[ -d {path} ] || {{ echo 'not a container' >&2; exit 1; }}; \
+         resolved=\"$(cd -- {path} && pwd -P)\" || exit 1; \
+         if [ \"$resolved\" = / ] || [ \"$resolved\" = \"$(cd && pwd -P)\" ]; then \
+         echo 'refusing to delete protected path' >&2; exit 1; fi{empty}"
+    )
+}
+
+// `cp -T` and `mv -T` are GNU-only, so an existing target container is handled explicitly:
+// copies are merged into it and moves replace it only while it's empty, like `rename` does.
+fn build_transfer_command(source: &Url, target: &Url, is_move: bool) -> Result<String> {
+    let source = shellwords::escape(&OpenSSHFascade::get_remote_path(source)?);
+    let target = shellwords::escape(&OpenSSHFascade::get_remote_path(target)?);
+    let guard = format!("[ -d {source} ] || {{ echo 'target is a container' >&2; exit 1; }}");
+    let command = match is_move {
+        true => format!(
+            "if [ -d {target} ]; then {guard}; rmdir -- {target} || exit 1; fi; \
+             mv -- {source} {target}"
+        ),
+        false => format!(
+            "if [ -d {target} ]; then {guard}; cp -R -- {source}/. {target}; \
+             else cp -R -- {source} {target}; fi"
+        ),
+    };
+    Ok(command)
+}
+
 impl OpenSSHFascade {
     pub fn copy(source: &Url, target: &Url) -> Result<()> {
         match source.scheme() {
@@ -62,8 +123,227 @@ impl OpenSSHFascade {
         )
     }
 
+    pub fn copy_on_remote(source: &Url, target: &Url) -> Result<()> {
+        Self::run_checked_command(source, &build_transfer_command(source, target, false)?)
+    }
+
+    pub fn move_on_remote(source: &Url, target: &Url) -> Result<()> {
+        Self::run_checked_command(source, &build_transfer_command(source, target, true)?)
+    }
+
+    pub fn create_remote_container(url: &Url) -> Result<()> {
+        Self::run_checked_path_command(url, "mkdir -p --")
+    }
+
+    pub fn delete_remote_file(url: &Url) -> Result<()> {
+        Self::run_checked_path_command(url, "rm --")
+    }
+
+    fn run_checked_path_command(url: &Url, command: &str) -> Result<()> {
+        let command = format!(
+            "{command} {}",
+            shellwords::escape(&Self::get_remote_path(url)?)
+        );
+        Self::run_checked_command(url, &command)
+    }
+
+    fn run_checked_command(url: &Url, command: &str) -> Result<()> {
+        let output = Self::run_remote_command(url, command)?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Could not run '{command}' on remote: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn get_remote_path(url: &Url) -> Result<String> {
+        let path = percent_decode_str(url.path()).decode_utf8()?;
+        Ok(path.to_string())
+    }
+
+    pub fn run_remote_command(url: &Url, command: &str) -> Result<Output> {
+        let Some(host) = url.host_str() else {
+            anyhow::bail!("Could not extract host from url");
+        };
+        let destination = match url.username() {
+            "" => host.to_string(),
+            user => format!("{user}@{host}"),
+        };
+        let mut ssh = Command::new("ssh");
+        if let Some(port) = url.port() {
+            ssh.args(["-p", &port.to_string()]);
+        }
+        let output = ssh.arg(destination).arg(command).output()?;
+        Ok(output)
+    }
+
+    pub fn hash_remote_file(url: &Url) -> Result<Option<String>> {
+        let path = shellwords::escape(&Self::get_remote_path(url)?);
+        let command = format!("if [ -e {path} ]; then {}; fi", build_hash_command(&path));
+        let output = Self::run_remote_command(url, &command)?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Could not hash remote file: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let stdout = String::from_utf8(output.stdout)?;
+        Ok(stdout.split_whitespace().next().map(String::from))
+    }
+
+    pub fn stat_remote_path(url: &Url) -> Result<UrlMetadata> {
+        let path = shellwords::escape(&Self::get_remote_path(url)?);
+        let command = format!(
+            "if [ -e {path} ]; then \
+             stat -L -c '%F|%s|%Y|%a' {path} 2>/dev/null || stat -L -f '%HT|%z|%m|%Lp' {path}; \
+             if [ -f {path} ]; then {}; fi; fi",
+            build_hash_command(&path)
+        );
+        let output = Self::run_remote_command(url, &command)?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Could not stat remote path: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let stdout = String::from_utf8(output.stdout)?;
+        let mut lines = stdout.lines();
+        let Some(stat) = lines.next() else {
+            return Ok(UrlMetadata::missing());
+        };
+        let fields: Vec<&str> = stat.split('|').collect();
+        let [file_type, size, modified, permissions] = fields[..] else {
+            anyhow::bail!("Could not parse remote stat output '{stat}'");
+        };
+        Ok(UrlMetadata {
+            exists: true,
+            is_container: file_type.eq_ignore_ascii_case("directory"),
+            size: size.parse().ok(),
+            modified: modified
+                .parse()
+                .ok()
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0)),
+            version: lines
+                .next()
+                .and_then(|line| line.split_whitespace().next())
+                .map(|hash| UrlVersion::Hash(hash.to_string())),
+            permissions: u32::from_str_radix(permissions, 8).ok(),
+        })
+    }
+
+    pub fn list_remote_path(
+        url: &Url,
+        max_depth: Option<usize>,
+        follow_symlinks: bool,
+    ) -> Result<Vec<(String, bool)>> {
+        let path = shellwords::escape(&Self::get_remote_path(url)?);
+        let follow = match follow_symlinks {
+            true => "-L ",
+            false => "",
+        };
+        let depth = match max_depth {
+            Some(max_depth) => format!("-maxdepth {max_depth} "),
+            None => String::new(),
+        };
+        let is_container = match follow_symlinks {
+            true => "[ -d \"$p\" ]",
+            false => "[ -d \"$p\" ] && [ ! -h \"$p\" ]",
+        };
+        // `-printf` is GNU only, the types and relative paths are printed by the shell instead.
+        let command = format!(
+            "find {follow}{path} -mindepth 1 {depth}-exec sh -c '\
+             for p; do r=${{p#\"$0\"}}; r=${{r#/}}; \
+             if {is_container}; then t=d; else t=f; fi; \
+             printf \"%s|%s\\0\" \"$t\" \"$r\"; done' {path} {{}} +"
+        );
+        let output = Self::run_remote_command(url, &command)?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Could not list remote path: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let stdout = String::from_utf8(output.stdout)?;
+        let mut entries = Vec::new();
+        for entry in stdout.split('\0').filter(|entry| !entry.is_empty()) {
+            let Some((file_type, path)) = entry.split_once('|') else {
+                anyhow::bail!("Could not parse remote listing entry '{entry}'");
+            };
+            entries.push((path.to_string(), file_type == "d"));
+        }
+        Ok(entries)
+    }
+
+    pub fn ensure_remote_container_can_be_deleted(url: &Url, recursive: bool) -> Result<()> {
+        let path = shellwords::escape(&Self::get_remote_path(url)?);
+        let command = build_container_guard(&path, recursive);
+        let output = Self::run_remote_command(url, &command)?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Could not delete remote container: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn delete_remote_container(url: &Url, recursive: bool) -> Result<()> {
+        let path = shellwords::escape(&Self::get_remote_path(url)?);
+        let delete = match recursive {
+            true => "rm -r --",
+            false => "rmdir --",
+        };
+        let command = format!("{}; {delete} {path}", build_container_guard(&path, recursive));
+        let output = Self::run_remote_command(url, &command)?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Could not delete remote container: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    // Locks left behind by crashed writers are broken once they are older than `stale_after`.
+    pub fn lock_remote_path(
+        url: &Url,
+        timeout: Duration,
+        stale_after: Duration,
+    ) -> Result<RemoteLock> {
+        let lock_path = format!("{}.lock", Self::get_remote_path(url)?);
+        let lock = shellwords::escape(&lock_path);
+        let minutes = stale_after.as_secs().div_ceil(60);
+        let command = format!(
+            "mkdir {lock} && exit 0; \
+             if [ -n \"$(find {lock} -prune -mmin +{minutes})\" ] && mv -- {lock} {lock}.stale.$$; then \
+             rmdir -- {lock}.stale.$$; fi; exit 1"
+        );
+        let deadline = Instant::now() + timeout;
+        loop {
+            let output = Self::run_remote_command(url, &command)?;
+            if output.status.success() {
+                return Ok(RemoteLock {
+                    url: url.clone(),
+                    lock_path,
+                });
+            }
+            if Instant::now() >= deadline {
+                anyhow::bail!(
+                    "Could not acquire lock '{lock_path}': {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            thread::sleep(LOCK_POLL_INTERVAL);
+        }
+    }
+
     fn copy_file(source: &OsStr, target: &OsStr) -> Result<()> {
-        Command::new("scp").args([source, target]).status()?;
+        let status = Command::new("scp").args([source, target]).status()?;
+        if !status.success() {
+            anyhow::bail!("scp exited with {status}");
+        }
         Ok(())
     }
 }
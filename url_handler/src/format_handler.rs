@@ -3,7 +3,7 @@ use serde::{de::DeserializeOwned, Serialize};
 use std::vec::Vec;
 
 pub trait FormatHandler<T: Serialize + DeserializeOwned> {
-    fn from_str(&self, data: &str) -> Result<T>;
+    fn parse_str(&self, data: &str) -> Result<T>;
     fn to_string(&self, record: &T) -> Result<String>;
 }
 
@@ -23,8 +23,8 @@ impl KnownFormatHandler {
 }
 
 impl<T: Serialize + DeserializeOwned> FormatHandler<T> for KnownFormatHandler {
-    fn from_str(&self, string: &str) -> Result<T> {
-        self.to_handler().from_str(string)
+    fn parse_str(&self, string: &str) -> Result<T> {
+        self.to_handler().parse_str(string)
     }
     fn to_string(&self, record: &T) -> Result<String> {
         self.to_handler().to_string(record)
@@ -61,7 +61,7 @@ impl FormatHandlerRegistry {
     pub fn get_handler_for_format(&self, format: &str) -> Option<&KnownFormatHandler> {
         let handler = match format {
             "toml" => &self.toml,
-            "json" => &self.toml,
+            "json" => &self.json,
             _ => return None,
         };
         Some(handler)
@@ -71,7 +71,7 @@ impl FormatHandlerRegistry {
 #[derive(Default, Clone, Debug)]
 pub struct TomlHandler {}
 impl<T: Serialize + DeserializeOwned> FormatHandler<T> for TomlHandler {
-    fn from_str(&self, string: &str) -> Result<T> {
+    fn parse_str(&self, string: &str) -> Result<T> {
         let record = toml::from_str(string)?;
         Ok(record)
     }
@@ -84,7 +84,7 @@ impl<T: Serialize + DeserializeOwned> FormatHandler<T> for TomlHandler {
 #[derive(Default, Clone, Debug)]
 pub struct JsonHandler {}
 impl<T: Serialize + DeserializeOwned> FormatHandler<T> for JsonHandler {
-    fn from_str(&self, string: &str) -> Result<T> {
+    fn parse_str(&self, string: &str) -> Result<T> {
         let record = serde_json::from_str(string)?;
         Ok(record)
     }
@@ -94,6 +94,17 @@ impl<T: Serialize + DeserializeOwned> FormatHandler<T> for JsonHandler {
     }
 }
 
+pub fn get_format_for_media_type(media_type: &str) -> Option<&'static str> {
+    let essence = media_type.split(';').next()?.trim().to_ascii_lowercase();
+    let format = match essence.as_str() {
+        "application/json" | "text/json" => "json",
+        "application/toml" | "text/toml" | "text/x-toml" | "application/x-toml" => "toml",
+        essence if essence.ends_with("+json") => "json",
+        _ => return None,
+    };
+    Some(format)
+}
+
 pub fn build_string_from_record_with_extension<T: Serialize + DeserializeOwned>(
     record: &T,
     extension: &str,
@@ -113,7 +124,7 @@ pub fn build_record_from_string_with_extension<T: Serialize + DeserializeOwned>(
     let Some(handler) = registry.get_handler_for_format(extension) else {
         anyhow::bail!("No handler for format '{extension} known!")
     };
-    handler.from_str(string)
+    handler.parse_str(string)
 }
 
 pub fn build_record_from_string<T: Serialize + DeserializeOwned>(
@@ -121,7 +132,7 @@ pub fn build_record_from_string<T: Serialize + DeserializeOwned>(
     registry: &FormatHandlerRegistry,
 ) -> Result<T> {
     for handler in registry.get_handlers() {
-        let Ok(record) = handler.from_str(string) else {
+        let Ok(record) = handler.parse_str(string) else {
             continue;
         };
         return Ok(record);
@@ -1,17 +1,36 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use percent_encoding::percent_decode_str;
 use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
 use url::Url;
 mod protocol_handler;
 pub use protocol_handler::{
     fetch_string_from_url, push_string_to_url, delete_string_from_url, create_empty_string_on_url,
-    create_url_container, list_urls_in_url_container,
-    try_build_url_from_path_buf, try_build_url_from_path_buf_with_hostname, ProtocolHandlerConfig,
+    delete_url_container,
+    fetch_bytes_from_url, push_bytes_to_url, update_bytes_at_url, fetch_bytes_with_version,
+    fetch_string_with_version, push_bytes_to_url_if_version, push_string_to_url_if_version,
+    stat_url, url_exists, walk_url_container, ConflictError, UrlMetadata, UrlVersion, WalkFilter,
+    WalkOptions, copy_url, move_url, sync_url_containers, SyncAction, SyncCompare, SyncOptions,
+    SyncSummary, get_trash_container, list_trash, purge_trash, restore_from_trash, TrashEntry,
+    fetch_version, list_versions, rollback, HistoryEntry, MirrorError,
+    ReplicationError, ReplicationOutcome, ReplicationPolicy, ReplicationReport,
+    create_url_container, list_urls_in_url_container, try_build_url_from_str,
+    try_build_url_from_path_buf, try_build_url_from_path_buf_with_hostname, try_build_url_from_unix_socket, DataUrl, ProtocolHandlerConfig,
     ProtocolHandlerRegistry,
 };
+mod compression_handler;
+use compression_handler::{
+    get_compression_handler_for_extension, CompressionHandler, KnownCompressionHandler,
+};
+mod encryption_handler;
+use encryption_handler::EncryptionHandler;
 mod external_fascade;
 mod format_handler;
 pub use format_handler::FormatHandlerRegistry;
-use format_handler::{build_record_from_string, build_string_from_record_with_extension};
+use format_handler::{
+    build_record_from_string, build_record_from_string_with_extension,
+    build_string_from_record_with_extension, get_format_for_media_type,
+};
 
 #[cfg(test)]
 mod tests;
@@ -21,33 +40,240 @@ pub fn build_record_from_url<T: Serialize + DeserializeOwned>(
     protocol_handlers: &ProtocolHandlerRegistry,
     format_handlers: &FormatHandlerRegistry,
 ) -> Result<T> {
-    let Some(string) = fetch_string_from_url(url, protocol_handlers)? else {
+    let Some(bytes) = fetch_bytes_from_url(url, protocol_handlers)? else {
         anyhow::bail!("Record at target location empty!");
     };
 
-    let record: T = build_record_from_string(&string, format_handlers)?;
+    let encoding = get_encoding_from_url(url, protocol_handlers)?;
+    let string = String::from_utf8(encoding.decode(bytes)?)?;
+
+    let record: T = match encoding.format {
+        Some(format) if format_handlers.get_handler_for_format(&format).is_some() => {
+            build_record_from_string_with_extension(&string, &format, format_handlers)?
+        }
+        _ => build_record_from_string(&string, format_handlers)?,
+    };
 
     Ok(record)
 }
 
+// Tries the URLs in order and returns the record together with the URL it was built from.
+pub fn build_record_from_mirrors<T: Serialize + DeserializeOwned>(
+    urls: &[Url],
+    protocol_handlers: &ProtocolHandlerRegistry,
+    format_handlers: &FormatHandlerRegistry,
+) -> Result<(T, Url)> {
+    if urls.is_empty() {
+        anyhow::bail!("No mirrors to build the record from!");
+    }
+    let mut errors = Vec::new();
+    for url in urls {
+        match build_record_from_url(url, protocol_handlers, format_handlers) {
+            Ok(record) => return Ok((record, url.clone())),
+            Err(error) => errors.push((url.clone(), error)),
+        }
+    }
+    Err(MirrorError::new(errors).into())
+}
+
+pub fn build_record_from_mirror_group<T: Serialize + DeserializeOwned>(
+    group: &str,
+    protocol_handlers: &ProtocolHandlerRegistry,
+    format_handlers: &FormatHandlerRegistry,
+) -> Result<(T, Url)> {
+    let Some(urls) = protocol_handlers.get_mirror_groups().get(group) else {
+        anyhow::bail!("Mirror group '{group}' is not configured in the ProtocolHandlerConfig!");
+    };
+    if urls.is_empty() {
+        anyhow::bail!("No mirrors configured for '{group}'");
+    }
+    build_record_from_mirrors(urls, protocol_handlers, format_handlers)
+}
+
 pub fn push_record_to_url<T: Serialize + DeserializeOwned>(
     url: &Url,
     record: &T,
     protocol_handlers: &ProtocolHandlerRegistry,
     format_handlers: &FormatHandlerRegistry,
 ) -> Result<()> {
-    let path = match url.to_file_path() {
-        Ok(path) => path,
-        Err(error) => anyhow::bail!("Can not serialize file format due to {:?}!", error),
-    };
-    let extension = match &path.extension() {
-        Some(os_str) => match os_str.to_str() {
-            Some(extension) => extension,
-            None => anyhow::bail!("Can not deserialize file format because no extension found!"),
+    let encoding = get_encoding_from_url(url, protocol_handlers)?;
+    let Some(format) = &encoding.format else {
+        match url.scheme() {
+            "stdio" => anyhow::bail!(
+                "Can not serialize record to stdio without a format, e.g. 'stdio:toml'!"
+            ),
+            _ => anyhow::bail!("Can not serialize file format because no extension found!"),
+        }
+    };
+
+    let string = build_string_from_record_with_extension(record, format, format_handlers)?;
+    let bytes = encoding.encode(string.into_bytes())?;
+    push_bytes_to_url(url, &bytes, protocol_handlers)
+}
+
+// Writes the record to every target and fails with a `ReplicationError` if the policy is not met.
+pub fn push_record_to_urls<T: Serialize + DeserializeOwned>(
+    urls: &[Url],
+    record: &T,
+    policy: &ReplicationPolicy,
+    protocol_handlers: &ProtocolHandlerRegistry,
+    format_handlers: &FormatHandlerRegistry,
+) -> Result<ReplicationReport> {
+    if urls.is_empty() {
+        anyhow::bail!("No targets to replicate the record to!");
+    }
+
+    // The record is only serialized once for every format used by the targets.
+    let mut strings: HashMap<String, String> = HashMap::new();
+    let mut push = |url: &Url| -> Result<()> {
+        let encoding = get_encoding_from_url(url, protocol_handlers)?;
+        let Some(format) = &encoding.format else {
+            anyhow::bail!("Can not serialize file format because no extension found!");
+        };
+        let string = match strings.get(format) {
+            Some(string) => string.clone(),
+            None => {
+                let string =
+                    build_string_from_record_with_extension(record, format, format_handlers)?;
+                strings.insert(format.clone(), string.clone());
+                string
+            }
+        };
+        let bytes = encoding.encode(string.into_bytes())?;
+        push_bytes_to_url(url, &bytes, protocol_handlers)
+    };
+
+    let mut report = ReplicationReport::default();
+    for url in urls {
+        let error = push(url).err().map(|error| format!("{error:#}"));
+        if let Some(error) = &error {
+            log::warn!("Could not replicate record to '{url}': {error}");
+        }
+        report.outcomes.push(ReplicationOutcome {
+            url: url.clone(),
+            error,
+        });
+    }
+
+    match report.satisfies(policy) {
+        true => Ok(report),
+        false => Err(ReplicationError::new(policy.clone(), report).into()),
+    }
+}
+
+pub fn update_record_at_url<T: Serialize + DeserializeOwned>(
+    url: &Url,
+    mut update: impl FnMut(&mut T) -> Result<()>,
+    protocol_handlers: &ProtocolHandlerRegistry,
+    format_handlers: &FormatHandlerRegistry,
+) -> Result<T> {
+    let encoding = get_encoding_from_url(url, protocol_handlers)?;
+    let Some(format) = &encoding.format else {
+        anyhow::bail!("Can not update record because no file format extension found!");
+    };
+
+    let mut updated_record: Option<T> = None;
+    update_bytes_at_url(
+        url,
+        &mut |bytes| {
+            let string = String::from_utf8(encoding.decode(bytes)?)?;
+            let mut record: T =
+                build_record_from_string_with_extension(&string, format, format_handlers)?;
+            update(&mut record)?;
+            let string = build_string_from_record_with_extension(&record, format, format_handlers)?;
+            updated_record = Some(record);
+            encoding.encode(string.into_bytes())
         },
-        None => anyhow::bail!("Can not deserialize file format because no extension found!"),
+        protocol_handlers,
+    )?;
+    updated_record.context("Record was not updated")
+}
+
+struct RecordEncoding<'a> {
+    format: Option<String>,
+    compressions: Vec<KnownCompressionHandler>,
+    encryption: Option<&'a EncryptionHandler>,
+}
+
+impl RecordEncoding<'_> {
+    fn encode(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        let mut bytes = bytes;
+        for handler in self.compressions.iter().rev() {
+            bytes = handler.compress(&bytes)?;
+        }
+        if let Some(handler) = self.encryption {
+            bytes = handler.encrypt(&bytes)?;
+        }
+        Ok(bytes)
+    }
+
+    fn decode(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        let mut bytes = bytes;
+        if let Some(handler) = self.encryption {
+            bytes = handler.decrypt(&bytes)?;
+        }
+        for handler in &self.compressions {
+            bytes = handler.decompress(&bytes)?;
+        }
+        Ok(bytes)
+    }
+}
+
+fn get_encoding_from_url<'a>(
+    url: &Url,
+    protocol_handlers: &'a ProtocolHandlerRegistry,
+) -> Result<RecordEncoding<'a>> {
+    let url = &protocol_handlers.get_mount_table().resolve(url)?;
+    let mut extensions = get_extensions_from_url(url)?;
+
+    let encryption_handler = protocol_handlers.get_encryption_handler();
+    let encryption = match extensions.last().map(String::as_str) {
+        Some("age") => {
+            extensions.pop();
+            Some(encryption_handler)
+        }
+        _ if encryption_handler.is_configured_for_url(url) => Some(encryption_handler),
+        _ => None,
     };
 
-    let string = build_string_from_record_with_extension(record, extension, format_handlers)?;
-    push_string_to_url(url, &string, protocol_handlers)
+    let mut compressions = Vec::new();
+    while let Some(handler) = extensions
+        .last()
+        .and_then(|extension| get_compression_handler_for_extension(extension))
+    {
+        extensions.pop();
+        compressions.push(handler);
+    }
+
+    Ok(RecordEncoding {
+        format: extensions.pop(),
+        compressions,
+        encryption,
+    })
+}
+
+fn get_extensions_from_url(url: &Url) -> Result<Vec<String>> {
+    let extensions = match url.scheme() {
+        "data" => get_format_for_media_type(DataUrl::try_from_url(url)?.media_type())
+            .map(String::from)
+            .into_iter()
+            .collect(),
+        "stdio" => url
+            .path()
+            .split('.')
+            .filter(|extension| !extension.is_empty())
+            .map(String::from)
+            .collect(),
+        _ => {
+            let path = percent_decode_str(url.path()).decode_utf8()?;
+            let file_name = path.rsplit('/').next().unwrap_or_default();
+            file_name
+                .trim_start_matches('.')
+                .split('.')
+                .skip(1)
+                .map(String::from)
+                .collect()
+        }
+    };
+    Ok(extensions)
 }
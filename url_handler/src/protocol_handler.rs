@@ -7,7 +7,46 @@ pub use file::{try_build_url_from_path_buf, FileProtocolHandler};
 mod scp;
 pub use scp::{try_build_url_from_path_buf_with_hostname, SCPProtocolHandler};
 mod http;
-pub use http::HttpProtocolHandler;
+pub use http::{try_build_url_from_unix_socket, HttpProtocolHandler};
+mod data;
+pub use data::{DataProtocolHandler, DataUrl};
+mod env;
+pub use env::EnvProtocolHandler;
+mod exec;
+pub use exec::ExecProtocolHandler;
+mod stdio;
+pub use stdio::{try_build_url_from_str, StdioProtocolHandler};
+mod version;
+pub use version::{ConflictError, UrlVersion};
+mod metadata;
+pub use metadata::UrlMetadata;
+mod walk;
+pub use walk::{WalkFilter, WalkOptions};
+mod transfer;
+pub use transfer::{copy_url, move_url};
+mod trash;
+pub use trash::{get_trash_container, list_trash, purge_trash, restore_from_trash, TrashEntry};
+mod history;
+pub use history::{fetch_version, list_versions, rollback, HistoryEntry};
+mod sync;
+pub use sync::{sync_url_containers, SyncAction, SyncCompare, SyncOptions, SyncSummary};
+mod archive;
+pub use archive::ArchiveProtocolHandler;
+mod git;
+pub use git::GitProtocolHandler;
+mod sqlite;
+pub use sqlite::SqliteProtocolHandler;
+mod redis;
+pub use redis::RedisProtocolHandler;
+mod consul;
+pub use consul::ConsulProtocolHandler;
+mod mirror;
+pub use mirror::MirrorError;
+mod mount;
+mod overlay;
+pub use overlay::OverlayProtocolHandler;
+mod replication;
+pub use replication::{ReplicationError, ReplicationOutcome, ReplicationPolicy, ReplicationReport};
 mod registry;
 pub use registry::{ProtocolHandlerConfig, ProtocolHandlerRegistry};
 
@@ -18,6 +57,61 @@ pub trait ProtocolHandler {
     fn create_empty_string_on_url(&self, url: &Url) -> Result<()>;
     fn create_url_container(&self, url: &Url) -> Result<()>;
     fn list_urls_in_url_container(&self, url: &Url) -> Result<HashSet<Url>>;
+
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        Ok(self.fetch_string_from_url(url)?.map(String::into_bytes))
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        self.push_string_to_url(url, std::str::from_utf8(bytes)?)
+    }
+    fn update_bytes_at_url(
+        &self,
+        url: &Url,
+        update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            anyhow::bail!("Record at target location empty!");
+        };
+        let bytes = update(bytes)?;
+        self.push_bytes_to_url(url, &bytes)
+    }
+    fn fetch_bytes_with_version(&self, _: &Url) -> Result<(Option<Vec<u8>>, Option<UrlVersion>)> {
+        anyhow::bail!("Versioned fetch is not supported by this handler!")
+    }
+    fn push_bytes_to_url_if_version(
+        &self,
+        _: &Url,
+        _: &[u8],
+        _: Option<&UrlVersion>,
+    ) -> Result<()> {
+        anyhow::bail!("Conditional push is not supported by this handler!")
+    }
+    fn stat(&self, _: &Url) -> Result<UrlMetadata> {
+        anyhow::bail!("Stat is not supported by this handler!")
+    }
+    fn walk_url_container(&self, url: &Url, options: &WalkOptions) -> Result<Vec<Url>> {
+        walk::walk_url_container_by_listing(self, url, options)
+    }
+    fn delete_url_container(&self, _: &Url, _: bool) -> Result<()> {
+        anyhow::bail!("Deleting containers is not supported by this handler!")
+    }
+    // Checked before containers are moved to the trash instead of being deleted.
+    fn ensure_container_can_be_deleted(&self, url: &Url, recursive: bool) -> Result<()> {
+        if !recursive && !self.list_urls_in_url_container(url)?.is_empty() {
+            anyhow::bail!("'{url}' is not empty");
+        }
+        Ok(())
+    }
+    fn try_copy_url(&self, _: &Url, _: &Url) -> Result<bool> {
+        Ok(false)
+    }
+    fn try_move_url(&self, _: &Url, _: &Url) -> Result<bool> {
+        Ok(false)
+    }
+    // Pseudo-schemes like stdio: have no location to keep history next to.
+    fn is_stored(&self) -> bool {
+        true
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -25,6 +119,16 @@ pub enum KnownProtocolHandler {
     File(FileProtocolHandler),
     Scp(SCPProtocolHandler),
     Http(HttpProtocolHandler),
+    Data(DataProtocolHandler),
+    Env(EnvProtocolHandler),
+    Stdio(StdioProtocolHandler),
+    Exec(ExecProtocolHandler),
+    Archive(ArchiveProtocolHandler),
+    Git(GitProtocolHandler),
+    Sqlite(SqliteProtocolHandler),
+    Redis(RedisProtocolHandler),
+    Consul(ConsulProtocolHandler),
+    Overlay(OverlayProtocolHandler),
 }
 
 impl KnownProtocolHandler {
@@ -33,6 +137,16 @@ impl KnownProtocolHandler {
             KnownProtocolHandler::File(handler) => handler as &dyn ProtocolHandler,
             KnownProtocolHandler::Scp(handler) => handler as &dyn ProtocolHandler,
             KnownProtocolHandler::Http(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Data(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Env(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Stdio(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Exec(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Archive(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Git(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Sqlite(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Redis(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Consul(handler) => handler as &dyn ProtocolHandler,
+            KnownProtocolHandler::Overlay(handler) => handler as &dyn ProtocolHandler,
         }
     }
 }
@@ -58,12 +172,60 @@ impl ProtocolHandler for KnownProtocolHandler {
     fn list_urls_in_url_container(&self, url: &Url) -> Result<HashSet<Url>> {
         self.to_handler().list_urls_in_url_container(url)
     }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        self.to_handler().fetch_bytes_from_url(url)
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        self.to_handler().push_bytes_to_url(url, bytes)
+    }
+    fn update_bytes_at_url(
+        &self,
+        url: &Url,
+        update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        self.to_handler().update_bytes_at_url(url, update)
+    }
+    fn fetch_bytes_with_version(&self, url: &Url) -> Result<(Option<Vec<u8>>, Option<UrlVersion>)> {
+        self.to_handler().fetch_bytes_with_version(url)
+    }
+    fn push_bytes_to_url_if_version(
+        &self,
+        url: &Url,
+        bytes: &[u8],
+        expected: Option<&UrlVersion>,
+    ) -> Result<()> {
+        self.to_handler()
+            .push_bytes_to_url_if_version(url, bytes, expected)
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        self.to_handler().stat(url)
+    }
+    fn walk_url_container(&self, url: &Url, options: &WalkOptions) -> Result<Vec<Url>> {
+        self.to_handler().walk_url_container(url, options)
+    }
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        self.to_handler().delete_url_container(url, recursive)
+    }
+    fn ensure_container_can_be_deleted(&self, url: &Url, recursive: bool) -> Result<()> {
+        self.to_handler()
+            .ensure_container_can_be_deleted(url, recursive)
+    }
+    fn try_copy_url(&self, source: &Url, target: &Url) -> Result<bool> {
+        self.to_handler().try_copy_url(source, target)
+    }
+    fn try_move_url(&self, source: &Url, target: &Url) -> Result<bool> {
+        self.to_handler().try_move_url(source, target)
+    }
+    fn is_stored(&self) -> bool {
+        self.to_handler().is_stored()
+    }
 }
 
 pub fn fetch_string_from_url(
     url: &Url,
     registry: &ProtocolHandlerRegistry,
 ) -> Result<Option<String>> {
+    let url = &registry.get_mount_table().resolve(url)?;
     let protocol = url.scheme();
     let Some(handler) = registry.get_handler_for_protocol(protocol) else {
         anyhow::bail!("Could not find handler for protocol '{protocol}'");
@@ -71,27 +233,193 @@ pub fn fetch_string_from_url(
     handler.fetch_string_from_url(url)
 }
 
+pub fn fetch_bytes_from_url(
+    url: &Url,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<Option<Vec<u8>>> {
+    let url = &registry.get_mount_table().resolve(url)?;
+    let protocol = url.scheme();
+    let Some(handler) = registry.get_handler_for_protocol(protocol) else {
+        anyhow::bail!("Could not find handler for protocol '{protocol}'");
+    };
+    handler.fetch_bytes_from_url(url)
+}
+
 pub fn push_string_to_url(
     url: &Url,
     string: &str,
     registry: &ProtocolHandlerRegistry,
 ) -> Result<()> {
+    let url = &registry.get_mount_table().resolve(url)?;
+    let protocol = url.scheme();
+    let Some(handler) = registry.get_handler_for_protocol(protocol) else {
+        anyhow::bail!("Could not find handler for protocol '{protocol}'");
+    };
+    if !handler.is_stored() || !registry.get_history_config().should_keep_history(url) {
+        return handler.push_string_to_url(url, string);
+    }
+    let previous = history::snapshot(url, registry)?;
+    handler.push_string_to_url(url, string)?;
+    history::keep_history(url, previous, registry)
+}
+
+pub fn push_bytes_to_url(
+    url: &Url,
+    bytes: &[u8],
+    registry: &ProtocolHandlerRegistry,
+) -> Result<()> {
+    let url = &registry.get_mount_table().resolve(url)?;
+    let protocol = url.scheme();
+    let Some(handler) = registry.get_handler_for_protocol(protocol) else {
+        anyhow::bail!("Could not find handler for protocol '{protocol}'");
+    };
+    if !handler.is_stored() || !registry.get_history_config().should_keep_history(url) {
+        return handler.push_bytes_to_url(url, bytes);
+    }
+    let previous = history::snapshot(url, registry)?;
+    handler.push_bytes_to_url(url, bytes)?;
+    history::keep_history(url, previous, registry)
+}
+
+pub fn update_bytes_at_url(
+    url: &Url,
+    update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<()> {
+    let url = &registry.get_mount_table().resolve(url)?;
+    let protocol = url.scheme();
+    let Some(handler) = registry.get_handler_for_protocol(protocol) else {
+        anyhow::bail!("Could not find handler for protocol '{protocol}'");
+    };
+    if !handler.is_stored() || !registry.get_history_config().should_keep_history(url) {
+        return handler.update_bytes_at_url(url, update);
+    }
+    // Handlers may retry the update, the input of the last attempt is what was replaced.
+    let mut previous = None;
+    handler.update_bytes_at_url(url, &mut |bytes| {
+        previous = Some(bytes.clone());
+        update(bytes)
+    })?;
+    history::keep_history(url, previous, registry)
+}
+
+pub fn fetch_bytes_with_version(
+    url: &Url,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<(Option<Vec<u8>>, Option<UrlVersion>)> {
+    let url = &registry.get_mount_table().resolve(url)?;
+    let protocol = url.scheme();
+    let Some(handler) = registry.get_handler_for_protocol(protocol) else {
+        anyhow::bail!("Could not find handler for protocol '{protocol}'");
+    };
+    handler.fetch_bytes_with_version(url)
+}
+
+pub fn fetch_string_with_version(
+    url: &Url,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<(Option<String>, Option<UrlVersion>)> {
+    let (bytes, version) = fetch_bytes_with_version(url, registry)?;
+    let string = match bytes {
+        Some(bytes) => Some(String::from_utf8(bytes)?),
+        None => None,
+    };
+    Ok((string, version))
+}
+
+pub fn push_bytes_to_url_if_version(
+    url: &Url,
+    bytes: &[u8],
+    expected: Option<&UrlVersion>,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<()> {
+    let url = &registry.get_mount_table().resolve(url)?;
+    let protocol = url.scheme();
+    let Some(handler) = registry.get_handler_for_protocol(protocol) else {
+        anyhow::bail!("Could not find handler for protocol '{protocol}'");
+    };
+    if !handler.is_stored() || !registry.get_history_config().should_keep_history(url) {
+        return handler.push_bytes_to_url_if_version(url, bytes, expected);
+    }
+    let previous = history::snapshot(url, registry)?;
+    handler.push_bytes_to_url_if_version(url, bytes, expected)?;
+    history::keep_history(url, previous, registry)
+}
+
+pub fn push_string_to_url_if_version(
+    url: &Url,
+    string: &str,
+    expected: Option<&UrlVersion>,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<()> {
+    push_bytes_to_url_if_version(url, string.as_bytes(), expected, registry)
+}
+
+pub fn stat_url(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<UrlMetadata> {
+    let url = &registry.get_mount_table().resolve(url)?;
+    let protocol = url.scheme();
+    let Some(handler) = registry.get_handler_for_protocol(protocol) else {
+        anyhow::bail!("Could not find handler for protocol '{protocol}'");
+    };
+    handler.stat(url)
+}
+
+pub fn url_exists(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<bool> {
+    Ok(stat_url(url, registry)?.exists)
+}
+
+pub fn walk_url_container(
+    url: &Url,
+    options: &WalkOptions,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<Vec<Url>> {
+    let mount_table = registry.get_mount_table();
+    let input = url;
+    let url = &mount_table.resolve(url)?;
+    let protocol = url.scheme();
+    let Some(handler) = registry.get_handler_for_protocol(protocol) else {
+        anyhow::bail!("Could not find handler for protocol '{protocol}'");
+    };
+    let urls = handler.walk_url_container(url, options)?;
+    Ok(urls.iter().map(|url| mount_table.to_alias(input, url)).collect())
+}
+
+pub fn delete_url_container(
+    url: &Url,
+    recursive: bool,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<()> {
+    let url = &registry.get_mount_table().resolve(url)?;
     let protocol = url.scheme();
     let Some(handler) = registry.get_handler_for_protocol(protocol) else {
         anyhow::bail!("Could not find handler for protocol '{protocol}'");
     };
-    handler.push_string_to_url(url, string)
+    if registry.get_trash_config().should_trash(url) {
+        handler.ensure_container_can_be_deleted(url, recursive)?;
+        trash::move_to_trash(url, registry)?;
+        return Ok(());
+    }
+    handler.delete_url_container(url, recursive)
 }
 
 pub fn delete_string_from_url(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<()> {
+    let url = &registry.get_mount_table().resolve(url)?;
     let protocol = url.scheme();
     let Some(handler) = registry.get_handler_for_protocol(protocol) else {
         anyhow::bail!("Could not find handler for protocol '{protocol}'");
     };
+    if registry.get_trash_config().should_trash(url) {
+        if walk::is_url_container(handler, url) {
+            anyhow::bail!("'{url}' is a container, not a record");
+        }
+        trash::move_to_trash(url, registry)?;
+        return Ok(());
+    }
     handler.delete_string_from_url(url)
 }
 
 pub fn create_empty_string_on_url(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<()> {
+    let url = &registry.get_mount_table().resolve(url)?;
     let protocol = url.scheme();
     let Some(handler) = registry.get_handler_for_protocol(protocol) else {
         anyhow::bail!("Could not find handler for protocol '{protocol}'");
@@ -100,6 +428,7 @@ pub fn create_empty_string_on_url(url: &Url, registry: &ProtocolHandlerRegistry)
 }
 
 pub fn create_url_container(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<()> {
+    let url = &registry.get_mount_table().resolve(url)?;
     let protocol = url.scheme();
     let Some(handler) = registry.get_handler_for_protocol(protocol) else {
         anyhow::bail!("Could not find handler for protocol '{protocol}'");
@@ -108,9 +437,13 @@ pub fn create_url_container(url: &Url, registry: &ProtocolHandlerRegistry) -> Re
 }
 
 pub fn list_urls_in_url_container(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<HashSet<Url>> {
+    let mount_table = registry.get_mount_table();
+    let input = url;
+    let url = &mount_table.resolve(url)?;
     let protocol = url.scheme();
     let Some(handler) = registry.get_handler_for_protocol(protocol) else {
         anyhow::bail!("Could not find handler for protocol '{protocol}'");
     };
-    handler.to_handler().list_urls_in_url_container(url)
+    let urls = handler.to_handler().list_urls_in_url_container(url)?;
+    Ok(urls.iter().map(|url| mount_table.to_alias(input, url)).collect())
 }
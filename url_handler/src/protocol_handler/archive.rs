@@ -0,0 +1,476 @@
+use super::registry::{ProtocolHandlerConfig, ProtocolHandlerRegistry};
+use super::{fetch_bytes_from_url, push_bytes_to_url, stat_url, ProtocolHandler, UrlMetadata};
+use crate::compression_handler::{
+    get_compression_handler_for_extension, CompressionHandler, KnownCompressionHandler,
+};
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use std::collections::HashSet;
+use std::io::{Cursor, Read, Write};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use url::Url;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+const MEMBER_SEPARATOR: &str = "!/";
+
+#[derive(Clone, Debug, PartialEq)]
+enum ArchiveFormat {
+    Zip,
+    Tar(Option<KnownCompressionHandler>),
+}
+
+// Where an entry came from, so rewriting the archive keeps what was not changed.
+#[derive(Clone, Debug)]
+enum EntryOrigin {
+    New,
+    Zip {
+        index: usize,
+        mode: Option<u32>,
+    },
+    Tar {
+        header: Box<tar::Header>,
+        link_name: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct ArchiveEntry {
+    name: String,
+    data: Option<Vec<u8>>,
+    origin: EntryOrigin,
+    is_modified: bool,
+}
+
+impl ArchiveEntry {
+    fn new(name: String, data: Option<Vec<u8>>) -> Self {
+        ArchiveEntry {
+            name,
+            data,
+            origin: EntryOrigin::New,
+            is_modified: true,
+        }
+    }
+
+    fn is_dir(&self) -> bool {
+        self.data.is_none() && self.name.ends_with('/')
+    }
+
+    fn is_file(&self) -> bool {
+        self.data.is_some()
+    }
+
+    fn set_data(&mut self, data: &[u8]) {
+        self.data = Some(data.to_vec());
+        self.is_modified = true;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ArchiveUrl {
+    kind: String,
+    format: ArchiveFormat,
+    archive: Url,
+    member: String,
+}
+
+impl ArchiveUrl {
+    fn try_from_url(url: &Url) -> Result<Self> {
+        let Some((kind, _)) = url.scheme().split_once('+') else {
+            anyhow::bail!("URL '{url}' does not name an archive format, e.g. 'zip+file'");
+        };
+        let inner = &url.as_str()[kind.len() + 1..];
+        let Some((archive, member)) = inner.split_once(MEMBER_SEPARATOR) else {
+            anyhow::bail!(
+                "URL '{url}' is missing the '{MEMBER_SEPARATOR}' separating archive and member"
+            );
+        };
+        let archive = Url::parse(archive)?;
+        let member = percent_decode_str(member).decode_utf8()?;
+        let member = member.trim_start_matches('/').to_string();
+
+        let format = match kind {
+            "zip" => ArchiveFormat::Zip,
+            "tar" => {
+                let file_name = archive.path().rsplit('/').next().unwrap_or_default();
+                let compression = match file_name.rsplit_once('.') {
+                    Some((_, "tgz")) => get_compression_handler_for_extension("gz"),
+                    Some((stem, extension)) if stem.ends_with(".tar") => {
+                        get_compression_handler_for_extension(extension)
+                    }
+                    _ => None,
+                };
+                ArchiveFormat::Tar(compression)
+            }
+            _ => anyhow::bail!("Archive format '{kind}' is not supported!"),
+        };
+
+        Ok(ArchiveUrl {
+            kind: kind.to_string(),
+            format,
+            archive,
+            member,
+        })
+    }
+
+    fn build_member_url(&self, member: &str) -> Result<Url> {
+        let url = Url::parse(&format!(
+            "{}+{}{MEMBER_SEPARATOR}{member}",
+            self.kind, self.archive
+        ))?;
+        Ok(url)
+    }
+
+    fn container_prefix(&self) -> String {
+        match self.member.trim_end_matches('/') {
+            "" => String::new(),
+            member => format!("{member}/"),
+        }
+    }
+}
+
+fn read_archive(format: &ArchiveFormat, bytes: &[u8]) -> Result<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+            for index in 0..archive.len() {
+                let mut file = archive.by_index(index)?;
+                let name = file.name().trim_start_matches("./").to_string();
+                let data = match file.is_dir() {
+                    true => None,
+                    false => {
+                        let mut data = Vec::new();
+                        file.read_to_end(&mut data)?;
+                        Some(data)
+                    }
+                };
+                entries.push(ArchiveEntry {
+                    name,
+                    data,
+                    origin: EntryOrigin::Zip {
+                        index,
+                        mode: file.unix_mode(),
+                    },
+                    is_modified: false,
+                });
+            }
+        }
+        ArchiveFormat::Tar(None) => read_tar_entries(bytes, &mut entries)?,
+        ArchiveFormat::Tar(Some(compression)) => {
+            read_tar_entries(&compression.decompress(bytes)?, &mut entries)?
+        }
+    }
+    Ok(entries)
+}
+
+fn read_tar_entries(bytes: &[u8], entries: &mut Vec<ArchiveEntry>) -> Result<()> {
+    let mut archive = tar::Archive::new(bytes);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = String::from_utf8_lossy(&entry.path_bytes())
+            .trim_start_matches("./")
+            .to_string();
+        let entry_type = entry.header().entry_type();
+        let (name, data) = match entry_type {
+            _ if entry_type.is_dir() => (format!("{}/", name.trim_end_matches('/')), None),
+            _ if entry_type.is_file() => {
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data)?;
+                (name, Some(data))
+            }
+            _ => (name, None),
+        };
+        entries.push(ArchiveEntry {
+            name,
+            data,
+            origin: EntryOrigin::Tar {
+                header: Box::new(entry.header().clone()),
+                link_name: entry.link_name()?.map(|link_name| link_name.into_owned()),
+            },
+            is_modified: false,
+        });
+    }
+    Ok(())
+}
+
+fn write_archive(
+    format: &ArchiveFormat,
+    original: &[u8],
+    entries: &[ArchiveEntry],
+) -> Result<Vec<u8>> {
+    let bytes = match format {
+        ArchiveFormat::Zip => {
+            let mut archive = match original.is_empty() {
+                true => None,
+                false => Some(ZipArchive::new(Cursor::new(original))?),
+            };
+            let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+            for entry in entries {
+                let mut options =
+                    SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+                if let EntryOrigin::Zip { index, mode } = &entry.origin {
+                    if let (Some(archive), false) = (&mut archive, entry.is_modified) {
+                        writer.raw_copy_file(archive.by_index_raw(*index)?)?;
+                        continue;
+                    }
+                    if let Some(mode) = mode {
+                        options = options.unix_permissions(*mode);
+                    }
+                }
+                match &entry.data {
+                    None => writer.add_directory(entry.name.as_str(), options)?,
+                    Some(data) => {
+                        writer.start_file(entry.name.as_str(), options)?;
+                        writer.write_all(data)?;
+                    }
+                }
+            }
+            writer.finish()?.into_inner()
+        }
+        ArchiveFormat::Tar(None) => write_tar_entries(entries)?,
+        ArchiveFormat::Tar(Some(compression)) => {
+            compression.compress(&write_tar_entries(entries)?)?
+        }
+    };
+    Ok(bytes)
+}
+
+fn write_tar_entries(entries: &[ArchiveEntry]) -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(Vec::new());
+    let modified = chrono::Utc::now().timestamp().max(0) as u64;
+    for entry in entries {
+        if let EntryOrigin::Tar { header, link_name } = &entry.origin {
+            let mut header = header.as_ref().clone();
+            if entry.is_modified {
+                header.set_mtime(modified);
+            }
+            match (&entry.data, link_name) {
+                (Some(data), _) => {
+                    header.set_size(data.len() as u64);
+                    builder.append_data(&mut header, &entry.name, data.as_slice())?;
+                }
+                (None, Some(link_name)) => {
+                    builder.append_link(&mut header, &entry.name, link_name)?
+                }
+                (None, None) => builder.append_data(&mut header, &entry.name, std::io::empty())?,
+            }
+            continue;
+        }
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(modified);
+        match &entry.data {
+            None => {
+                header.set_entry_type(tar::EntryType::Directory);
+                header.set_mode(0o755);
+                header.set_size(0);
+                builder.append_data(&mut header, &entry.name, std::io::empty())?;
+            }
+            Some(data) => {
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_mode(0o644);
+                header.set_size(data.len() as u64);
+                builder.append_data(&mut header, &entry.name, data.as_slice())?;
+            }
+        }
+    }
+    Ok(builder.into_inner()?)
+}
+
+#[derive(Clone, Debug)]
+pub struct ArchiveProtocolHandler {
+    config: Box<ProtocolHandlerConfig>,
+    // Built on first use, as the registry holds this handler itself.
+    registry: OnceLock<Box<ProtocolHandlerRegistry>>,
+}
+
+impl ArchiveProtocolHandler {
+    pub fn new(config: &ProtocolHandlerConfig) -> Self {
+        ArchiveProtocolHandler {
+            config: Box::new(config.clone()),
+            registry: OnceLock::new(),
+        }
+    }
+
+    fn registry(&self) -> &ProtocolHandlerRegistry {
+        self.registry
+            .get_or_init(|| Box::new(ProtocolHandlerRegistry::new(&self.config)))
+    }
+
+    fn read_entries(&self, archive_url: &ArchiveUrl) -> Result<(Vec<u8>, Vec<ArchiveEntry>)> {
+        let Some(bytes) = fetch_bytes_from_url(&archive_url.archive, self.registry())? else {
+            anyhow::bail!("Archive at '{}' is empty!", archive_url.archive);
+        };
+        let entries = read_archive(&archive_url.format, &bytes)?;
+        Ok((bytes, entries))
+    }
+
+    // Writes create the archive when it does not exist yet.
+    fn read_entries_or_empty(
+        &self,
+        archive_url: &ArchiveUrl,
+    ) -> Result<(Vec<u8>, Vec<ArchiveEntry>)> {
+        match stat_url(&archive_url.archive, self.registry())?.exists {
+            true => self.read_entries(archive_url),
+            false => Ok((Vec::new(), Vec::new())),
+        }
+    }
+
+    fn write_entries(
+        &self,
+        archive_url: &ArchiveUrl,
+        original: &[u8],
+        entries: &[ArchiveEntry],
+    ) -> Result<()> {
+        let bytes = write_archive(&archive_url.format, original, entries)?;
+        push_bytes_to_url(&archive_url.archive, &bytes, self.registry())
+    }
+}
+
+impl ProtocolHandler for ArchiveProtocolHandler {
+    fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            return Ok(None);
+        };
+        Ok(Some(String::from_utf8(bytes)?))
+    }
+    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+        self.push_bytes_to_url(url, string.as_bytes())
+    }
+    fn delete_string_from_url(&self, url: &Url) -> Result<()> {
+        let archive_url = ArchiveUrl::try_from_url(url)?;
+        let (original, mut entries) = self.read_entries(&archive_url)?;
+        let Some(index) = entries
+            .iter()
+            .position(|entry| !entry.is_dir() && entry.name == archive_url.member)
+        else {
+            anyhow::bail!("Could not find member '{}' in archive", archive_url.member);
+        };
+        entries.remove(index);
+        self.write_entries(&archive_url, &original, &entries)
+    }
+    fn create_empty_string_on_url(&self, url: &Url) -> Result<()> {
+        self.push_string_to_url(url, "")
+    }
+    fn create_url_container(&self, url: &Url) -> Result<()> {
+        let archive_url = ArchiveUrl::try_from_url(url)?;
+        let prefix = archive_url.container_prefix();
+        let (original, mut entries) = self.read_entries_or_empty(&archive_url)?;
+        if !prefix.is_empty() {
+            if entries.iter().any(|entry| entry.name == prefix) {
+                return Ok(());
+            }
+            entries.push(ArchiveEntry::new(prefix, None));
+        }
+        self.write_entries(&archive_url, &original, &entries)
+    }
+    fn list_urls_in_url_container(&self, url: &Url) -> Result<HashSet<Url>> {
+        let archive_url = ArchiveUrl::try_from_url(url)?;
+        let prefix = archive_url.container_prefix();
+        let mut children: HashSet<String> = HashSet::default();
+        let (_, entries) = self.read_entries(&archive_url)?;
+        for entry in entries {
+            let Some(rest) = entry.name.strip_prefix(&prefix) else {
+                continue;
+            };
+            let child = match rest.split_once('/') {
+                Some((directory, _)) => format!("{directory}/"),
+                None => rest.to_string(),
+            };
+            if child.is_empty() || child == "/" {
+                continue;
+            }
+            children.insert(format!("{prefix}{child}"));
+        }
+
+        let mut urls: HashSet<Url> = HashSet::default();
+        for child in children {
+            urls.insert(archive_url.build_member_url(&child)?);
+        }
+        Ok(urls)
+    }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        let archive_url = ArchiveUrl::try_from_url(url)?;
+        let (_, entries) = self.read_entries(&archive_url)?;
+        let Some(entry) = entries
+            .into_iter()
+            .find(|entry| !entry.is_dir() && entry.name == archive_url.member)
+        else {
+            anyhow::bail!("Could not find member '{}' in archive", archive_url.member);
+        };
+        if !entry.is_file() {
+            anyhow::bail!("Member '{}' is not a regular file", archive_url.member);
+        }
+        Ok(entry.data)
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        let archive_url = ArchiveUrl::try_from_url(url)?;
+        if archive_url.member.is_empty() || archive_url.member.ends_with('/') {
+            anyhow::bail!("Can not push a string to an archive directory!");
+        }
+        let (original, mut entries) = self.read_entries_or_empty(&archive_url)?;
+        match entries
+            .iter_mut()
+            .find(|entry| !entry.is_dir() && entry.name == archive_url.member)
+        {
+            Some(entry) if !entry.is_file() => {
+                anyhow::bail!("Member '{}' is not a regular file", archive_url.member)
+            }
+            Some(entry) => entry.set_data(bytes),
+            None => entries.push(ArchiveEntry::new(
+                archive_url.member.clone(),
+                Some(bytes.to_vec()),
+            )),
+        }
+        self.write_entries(&archive_url, &original, &entries)
+    }
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        let archive_url = ArchiveUrl::try_from_url(url)?;
+        let prefix = archive_url.container_prefix();
+        if prefix.is_empty() {
+            anyhow::bail!("Can not delete the archive root, delete '{}' instead", archive_url.archive);
+        }
+        let (original, mut entries) = self.read_entries(&archive_url)?;
+        let is_empty = entries
+            .iter()
+            .all(|entry| entry.name == prefix || !entry.name.starts_with(&prefix));
+        if !recursive && !is_empty {
+            anyhow::bail!("Container '{prefix}' in archive is not empty!");
+        }
+        entries.retain(|entry| !entry.name.starts_with(&prefix));
+        self.write_entries(&archive_url, &original, &entries)
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        let archive_url = ArchiveUrl::try_from_url(url)?;
+        if !stat_url(&archive_url.archive, self.registry())?.exists {
+            return Ok(UrlMetadata::missing());
+        }
+        let prefix = archive_url.container_prefix();
+        if prefix.is_empty() {
+            return Ok(UrlMetadata {
+                exists: true,
+                is_container: true,
+                ..UrlMetadata::default()
+            });
+        }
+        let (_, entries) = self.read_entries(&archive_url)?;
+        if let Some(entry) = entries
+            .iter()
+            .find(|entry| !entry.is_dir() && entry.name == archive_url.member)
+        {
+            return Ok(UrlMetadata {
+                exists: true,
+                size: entry.data.as_ref().map(|data| data.len() as u64),
+                ..UrlMetadata::default()
+            });
+        }
+        match entries.iter().any(|entry| entry.name.starts_with(&prefix)) {
+            true => Ok(UrlMetadata {
+                exists: true,
+                is_container: true,
+                ..UrlMetadata::default()
+            }),
+            false => Ok(UrlMetadata::missing()),
+        }
+    }
+}
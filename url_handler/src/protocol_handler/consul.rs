@@ -0,0 +1,260 @@
+use super::http::{backoff_after_conflict, HttpMethod};
+use super::walk::build_child_url;
+use super::{ConflictError, HttpProtocolHandler, ProtocolHandler, UrlMetadata, UrlVersion};
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use percent_encoding::percent_decode_str;
+use reqwest::blocking::Response;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use url::Url;
+
+const DEFAULT_PORT: u16 = 8500;
+const MAX_UPDATE_ATTEMPTS: u32 = 10;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ConsulProtocolHandlerConfig {
+    #[serde(default)]
+    https: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ConsulEntry {
+    modify_index: u64,
+    value: Option<String>,
+}
+
+// Headers and auth come from the http config of the Consul host, its methods are not used.
+#[derive(Default, Clone, Debug)]
+pub struct ConsulProtocolHandler {
+    https: bool,
+    http_handler: HttpProtocolHandler,
+}
+
+impl ConsulProtocolHandler {
+    pub fn new(config: &ConsulProtocolHandlerConfig, http_handler: HttpProtocolHandler) -> Self {
+        ConsulProtocolHandler {
+            https: config.https,
+            http_handler,
+        }
+    }
+
+    fn build_api_url(&self, url: &Url, query: Option<&str>) -> Result<Url> {
+        let Some(host) = url.host_str() else {
+            anyhow::bail!("Could not extract host from url");
+        };
+        let path = url.path();
+        let Some(key) = path
+            .strip_prefix("/kv/")
+            .or_else(|| (path == "/kv").then_some(""))
+        else {
+            anyhow::bail!("Consul URL '{url}' must point below /kv/");
+        };
+        let scheme = match self.https {
+            true => "https",
+            false => "http",
+        };
+        let port = url.port().unwrap_or(DEFAULT_PORT);
+        let mut api_url = Url::parse(&format!("{scheme}://{host}:{port}/v1/kv/{key}"))?;
+        api_url.set_query(query);
+        Ok(api_url)
+    }
+
+    fn send(&self, url: &Url, method: HttpMethod, query: Option<&str>) -> Result<Response> {
+        let api_url = self.build_api_url(url, query)?;
+        Ok(self
+            .http_handler
+            .build_request_with_method(&api_url, method)?
+            .send()?)
+    }
+
+    fn fetch_entry(&self, url: &Url) -> Result<Option<ConsulEntry>> {
+        let response = self.send(url, HttpMethod::Get, None)?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let entries: Vec<ConsulEntry> =
+            serde_json::from_slice(&response.error_for_status()?.bytes()?)?;
+        Ok(entries.into_iter().next())
+    }
+
+    fn list_keys(&self, url: &Url, separator: bool) -> Result<Vec<String>> {
+        let query = match separator {
+            true => "keys&separator=/",
+            false => "keys",
+        };
+        let response = self.send(&build_prefix_url(url), HttpMethod::Get, Some(query))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_slice(
+            &response.error_for_status()?.bytes()?,
+        )?)
+    }
+
+    fn get_prefix(url: &Url) -> String {
+        let prefix = url.path().strip_prefix("/kv").unwrap_or_default();
+        let prefix = percent_decode_str(prefix.trim_start_matches('/'))
+            .decode_utf8_lossy()
+            .trim_end_matches('/')
+            .to_string();
+        match prefix.is_empty() {
+            true => prefix,
+            false => format!("{prefix}/"),
+        }
+    }
+}
+
+fn build_prefix_url(url: &Url) -> Url {
+    let mut prefix_url = url.clone();
+    if !url.path().ends_with('/') {
+        prefix_url.set_path(&format!("{}/", url.path()));
+    }
+    prefix_url
+}
+
+fn decode_value(entry: &ConsulEntry) -> Result<Vec<u8>> {
+    match &entry.value {
+        Some(value) => Ok(STANDARD.decode(value)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+impl ProtocolHandler for ConsulProtocolHandler {
+    fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            return Ok(None);
+        };
+        Ok(Some(String::from_utf8(bytes)?))
+    }
+    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+        self.push_bytes_to_url(url, string.as_bytes())
+    }
+    fn delete_string_from_url(&self, url: &Url) -> Result<()> {
+        self.send(url, HttpMethod::Delete, None)?
+            .error_for_status()?;
+        Ok(())
+    }
+    fn create_empty_string_on_url(&self, url: &Url) -> Result<()> {
+        self.push_bytes_to_url(url, &[])
+    }
+    fn create_url_container(&self, _: &Url) -> Result<()> {
+        // Containers are key prefixes and exist as soon as a key below them does.
+        Ok(())
+    }
+    fn list_urls_in_url_container(&self, url: &Url) -> Result<HashSet<Url>> {
+        let prefix = Self::get_prefix(url);
+        let mut urls: HashSet<Url> = HashSet::default();
+        for key in self.list_keys(url, true)? {
+            let Some(child) = key.strip_prefix(&prefix).filter(|child| !child.is_empty()) else {
+                continue;
+            };
+            urls.insert(build_child_url(&build_prefix_url(url), child)?);
+        }
+        Ok(urls)
+    }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        let response = self.send(url, HttpMethod::Get, Some("raw"))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.bytes()?.to_vec()))
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        let api_url = self.build_api_url(url, None)?;
+        self.http_handler
+            .build_request_with_method(&api_url, HttpMethod::Put)?
+            .body(bytes.to_vec())
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+    fn update_bytes_at_url(
+        &self,
+        url: &Url,
+        update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        for attempt in 0..MAX_UPDATE_ATTEMPTS {
+            let (Some(bytes), version) = self.fetch_bytes_with_version(url)? else {
+                anyhow::bail!("Record at target location empty!");
+            };
+            let bytes = update(bytes)?;
+            match self.push_bytes_to_url_if_version(url, &bytes, version.as_ref()) {
+                Err(error) if error.is::<ConflictError>() => backoff_after_conflict(attempt),
+                result => return result,
+            }
+        }
+        anyhow::bail!("Could not update '{url}' because it kept changing concurrently!")
+    }
+    fn fetch_bytes_with_version(&self, url: &Url) -> Result<(Option<Vec<u8>>, Option<UrlVersion>)> {
+        let Some(entry) = self.fetch_entry(url)? else {
+            return Ok((None, None));
+        };
+        Ok((
+            Some(decode_value(&entry)?),
+            Some(UrlVersion::Index(entry.modify_index)),
+        ))
+    }
+    fn push_bytes_to_url_if_version(
+        &self,
+        url: &Url,
+        bytes: &[u8],
+        expected: Option<&UrlVersion>,
+    ) -> Result<()> {
+        // A check-and-set index of 0 only succeeds if the key does not exist yet.
+        let index = match expected {
+            Some(UrlVersion::Index(index)) => *index,
+            Some(version) => anyhow::bail!("Consul can only compare indices, not {version:?}"),
+            None => 0,
+        };
+        let api_url = self.build_api_url(url, Some(&format!("cas={index}")))?;
+        let response = self
+            .http_handler
+            .build_request_with_method(&api_url, HttpMethod::Put)?
+            .body(bytes.to_vec())
+            .send()?
+            .error_for_status()?;
+        if response.text()?.trim() != "true" {
+            let found = self
+                .fetch_entry(url)?
+                .map(|entry| UrlVersion::Index(entry.modify_index));
+            return Err(ConflictError::new(url, expected, found.as_ref()).into());
+        }
+        Ok(())
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        if !url.path().ends_with('/') {
+            if let Some(entry) = self.fetch_entry(url)? {
+                return Ok(UrlMetadata {
+                    exists: true,
+                    size: Some(decode_value(&entry)?.len() as u64),
+                    version: Some(UrlVersion::Index(entry.modify_index)),
+                    ..UrlMetadata::default()
+                });
+            }
+        }
+        let exists = Self::get_prefix(url).is_empty() || !self.list_keys(url, true)?.is_empty();
+        Ok(UrlMetadata {
+            exists,
+            is_container: exists,
+            ..UrlMetadata::default()
+        })
+    }
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        if Self::get_prefix(url).is_empty() {
+            anyhow::bail!("Refusing to delete every key of '{url}'");
+        }
+        let keys = self.list_keys(url, false)?;
+        if keys.is_empty() {
+            return Ok(());
+        }
+        if !recursive {
+            anyhow::bail!("Container '{url}' is not empty!");
+        }
+        self.send(&build_prefix_url(url), HttpMethod::Delete, Some("recurse"))?
+            .error_for_status()?;
+        Ok(())
+    }
+}
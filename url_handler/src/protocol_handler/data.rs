@@ -0,0 +1,116 @@
+use super::{ProtocolHandler, UrlMetadata};
+use anyhow::Result;
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use percent_encoding::percent_decode_str;
+use std::collections::HashSet;
+use url::Url;
+
+const DEFAULT_MEDIA_TYPE: &str = "text/plain";
+
+const BASE64_ENGINE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataUrl {
+    media_type: String,
+    data: Vec<u8>,
+}
+
+impl DataUrl {
+    pub fn try_from_url(url: &Url) -> Result<Self> {
+        let Some(content) = url.as_str().strip_prefix("data:") else {
+            anyhow::bail!("URL '{url}' is not a data URL");
+        };
+        let content = match content.split_once('#') {
+            Some((content, _fragment)) => content,
+            None => content,
+        };
+        let Some((header, payload)) = content.split_once(',') else {
+            anyhow::bail!("Data URL is missing the ',' separating header and payload");
+        };
+
+        let mut parameters: Vec<&str> = header.split(';').map(str::trim).collect();
+        let is_base64 = parameters
+            .last()
+            .is_some_and(|parameter| parameter.eq_ignore_ascii_case("base64"));
+        if is_base64 {
+            parameters.pop();
+        }
+        let media_type = match parameters.first() {
+            Some(media_type) if media_type.contains('/') => media_type.to_ascii_lowercase(),
+            _ => DEFAULT_MEDIA_TYPE.to_string(),
+        };
+
+        let payload: Vec<u8> = percent_decode_str(payload).collect();
+        let data = match is_base64 {
+            true => {
+                let payload: Vec<u8> = payload
+                    .into_iter()
+                    .filter(|byte| !byte.is_ascii_whitespace())
+                    .collect();
+                BASE64_ENGINE.decode(payload)?
+            }
+            false => payload,
+        };
+
+        Ok(DataUrl { media_type, data })
+    }
+
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct DataProtocolHandler {}
+
+impl ProtocolHandler for DataProtocolHandler {
+    fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let data_url = DataUrl::try_from_url(url)?;
+        let string = String::from_utf8(data_url.data)?;
+        Ok(Some(string))
+    }
+    fn push_string_to_url(&self, _: &Url, _: &str) -> Result<()> {
+        anyhow::bail!("Data URLs are read-only and can not be pushed to!")
+    }
+    fn delete_string_from_url(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Data URLs are read-only and can not be deleted!")
+    }
+    fn create_empty_string_on_url(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Data URLs are read-only and can not be created!")
+    }
+    fn create_url_container(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Data URLs can not be used as containers!")
+    }
+    fn list_urls_in_url_container(&self, _: &Url) -> Result<HashSet<Url>> {
+        anyhow::bail!("Data URLs can not be used as containers!")
+    }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        let data_url = DataUrl::try_from_url(url)?;
+        Ok(Some(data_url.data))
+    }
+    fn push_bytes_to_url(&self, _: &Url, _: &[u8]) -> Result<()> {
+        anyhow::bail!("Data URLs are read-only and can not be pushed to!")
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        let data_url = DataUrl::try_from_url(url)?;
+        Ok(UrlMetadata {
+            exists: true,
+            size: Some(data_url.data.len() as u64),
+            ..UrlMetadata::default()
+        })
+    }
+    fn is_stored(&self) -> bool {
+        false
+    }
+}
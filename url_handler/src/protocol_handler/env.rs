@@ -0,0 +1,51 @@
+use super::{ProtocolHandler, UrlMetadata};
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use std::collections::HashSet;
+use std::env::{var, VarError};
+use url::Url;
+
+#[derive(Default, Clone, Debug)]
+pub struct EnvProtocolHandler {}
+
+impl ProtocolHandler for EnvProtocolHandler {
+    fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let name = percent_decode_str(url.path()).decode_utf8()?;
+        if name.is_empty() {
+            anyhow::bail!("Could not find variable name in URL '{url}'");
+        }
+        match var(name.as_ref()) {
+            Ok(string) => Ok(Some(string)),
+            Err(VarError::NotPresent) => Ok(None),
+            Err(error) => anyhow::bail!("Could not read variable '{name}' due to {error}"),
+        }
+    }
+    fn push_string_to_url(&self, _: &Url, _: &str) -> Result<()> {
+        anyhow::bail!("Environment variables are read-only and can not be pushed to!")
+    }
+    fn delete_string_from_url(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Environment variables are read-only and can not be deleted!")
+    }
+    fn create_empty_string_on_url(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Environment variables are read-only and can not be created!")
+    }
+    fn create_url_container(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Environment variables can not be used as containers!")
+    }
+    fn list_urls_in_url_container(&self, _: &Url) -> Result<HashSet<Url>> {
+        anyhow::bail!("Environment variables can not be used as containers!")
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        let Some(string) = self.fetch_string_from_url(url)? else {
+            return Ok(UrlMetadata::missing());
+        };
+        Ok(UrlMetadata {
+            exists: true,
+            size: Some(string.len() as u64),
+            ..UrlMetadata::default()
+        })
+    }
+    fn is_stored(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,94 @@
+use super::ProtocolHandler;
+use crate::external_fascade::CommandFascade;
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use url::Url;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ExecProtocolHandlerConfig {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default, with = "humantime_serde")]
+    timeout: Option<Duration>,
+    commands: Option<HashMap<String, CommandConfig>>,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+struct CommandConfig {
+    fetch: Option<String>,
+    push: Option<String>,
+    #[serde(default, with = "humantime_serde")]
+    timeout: Option<Duration>,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct ExecProtocolHandler {
+    enabled: bool,
+    timeout: Duration,
+    commands: HashMap<String, CommandConfig>,
+}
+
+impl ExecProtocolHandler {
+    pub fn new(config: &ExecProtocolHandlerConfig) -> Self {
+        ExecProtocolHandler {
+            enabled: config.enabled,
+            timeout: config.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            commands: match &config.commands {
+                None => HashMap::default(),
+                Some(map) => map.clone(),
+            },
+        }
+    }
+
+    fn get_command_config(&self, url: &Url) -> Result<&CommandConfig> {
+        if !self.enabled {
+            anyhow::bail!("The exec handler is disabled, enable it in the ProtocolHandlerConfig!");
+        }
+        let name = percent_decode_str(url.path()).decode_utf8()?;
+        let Some(config) = self.commands.get(name.as_ref()) else {
+            anyhow::bail!("No command named '{name}' is configured for the exec handler");
+        };
+        Ok(config)
+    }
+}
+
+impl ProtocolHandler for ExecProtocolHandler {
+    fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let config = self.get_command_config(url)?;
+        let Some(command_line) = &config.fetch else {
+            anyhow::bail!("No fetch command is configured for '{url}'");
+        };
+        let timeout = config.timeout.unwrap_or(self.timeout);
+        let string = CommandFascade::run(command_line, None, timeout)?;
+        Ok(Some(string))
+    }
+    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+        let config = self.get_command_config(url)?;
+        let Some(command_line) = &config.push else {
+            anyhow::bail!("No push command is configured for '{url}'");
+        };
+        let timeout = config.timeout.unwrap_or(self.timeout);
+        CommandFascade::run(command_line, Some(string), timeout)?;
+        Ok(())
+    }
+    fn delete_string_from_url(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Delete Operation is not supported by the exec handler!")
+    }
+    fn create_empty_string_on_url(&self, url: &Url) -> Result<()> {
+        self.push_string_to_url(url, "")
+    }
+    fn create_url_container(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Commands can not be used as containers!")
+    }
+    fn list_urls_in_url_container(&self, _: &Url) -> Result<HashSet<Url>> {
+        anyhow::bail!("Commands can not be used as containers!")
+    }
+    fn is_stored(&self) -> bool {
+        false
+    }
+}
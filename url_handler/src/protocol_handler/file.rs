@@ -1,14 +1,65 @@
 use std::collections::HashSet;
-use super::ProtocolHandler;
+use super::walk::{WalkEntry, WalkMatcher};
+use super::{ConflictError, ProtocolHandler, UrlMetadata, UrlVersion, WalkOptions};
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use path_absolutize::*;
-use std::fs::{create_dir_all, read_to_string, write, remove_file};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{
+    copy, create_dir_all, metadata, read, read_link, read_to_string, remove_dir, remove_dir_all,
+    remove_file, rename, symlink_metadata, File, Metadata, OpenOptions, Permissions,
+};
+use std::io::{ErrorKind, Write};
 use std::path::Path;
 use std::path::PathBuf;
+use tempfile::Builder;
 use url::Url;
 
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FileProtocolHandlerConfig {
+    #[serde(default)]
+    sync_parent_directory: bool,
+}
+
 #[derive(Default, Clone, Debug)]
-pub struct FileProtocolHandler {}
+pub struct FileProtocolHandler {
+    sync_parent_directory: bool,
+}
+
+impl FileProtocolHandler {
+    pub fn new(config: &FileProtocolHandlerConfig) -> Self {
+        FileProtocolHandler {
+            sync_parent_directory: config.sync_parent_directory,
+        }
+    }
+}
+
+const MAX_SYMLINK_DEPTH: usize = 40;
+
+// Follows symlinks so the record is replaced at its real location, not the link.
+fn resolve_symlinks(path: &Path) -> Result<PathBuf> {
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_SYMLINK_DEPTH {
+        match symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target = read_link(&path)?;
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            }
+            _ => return Ok(path),
+        }
+    }
+    anyhow::bail!("Too many levels of symbolic links at '{}'", path.display())
+}
+
+#[cfg(unix)]
+fn default_permissions() -> Permissions {
+    use std::os::unix::fs::PermissionsExt;
+    Permissions::from_mode(0o666)
+}
 
 impl ProtocolHandler for FileProtocolHandler {
     fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
@@ -19,18 +70,7 @@ impl ProtocolHandler for FileProtocolHandler {
         Ok(Some(string))
     }
     fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
-        let Ok(path) = url.to_file_path() else {
-            anyhow::bail!("Could not parse URL to path");
-        };
-
-        let target_dir: &Path = path
-            .parent()
-            .context("Could not find target directory for {url}")?;
-        if !target_dir.exists() {
-            create_dir_all(target_dir)?;
-        }
-        write(path, string)?;
-        Ok(())
+        self.push_bytes_to_url(url, string.as_bytes())
     }
 
     fn delete_string_from_url(&self, url: &Url) -> Result<()> {
@@ -70,6 +110,283 @@ impl ProtocolHandler for FileProtocolHandler {
         }
         Ok(urls)
     }
+
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        let Ok(path) = url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        self.ensure_container_can_be_deleted(url, true)?;
+        match recursive {
+            true => remove_dir_all(path)?,
+            false => remove_dir(path)?,
+        }
+        Ok(())
+    }
+
+    fn ensure_container_can_be_deleted(&self, url: &Url, recursive: bool) -> Result<()> {
+        let Ok(path) = url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        if !path.is_dir() {
+            anyhow::bail!("'{}' is not a container", path.display());
+        }
+        if is_protected_path(&path)? {
+            anyhow::bail!("Refusing to delete protected path '{}'", path.display());
+        }
+        if !recursive && path.read_dir()?.next().is_some() {
+            anyhow::bail!("'{}' is not empty", path.display());
+        }
+        Ok(())
+    }
+
+    fn try_copy_url(&self, source: &Url, target: &Url) -> Result<bool> {
+        if source.scheme() != "file" || target.scheme() != "file" {
+            return Ok(false);
+        }
+        let (Ok(source), Ok(target)) = (source.to_file_path(), target.to_file_path()) else {
+            return Ok(false);
+        };
+        if source.is_dir() {
+            return Ok(false);
+        }
+        copy(source, target)?;
+        Ok(true)
+    }
+
+    fn try_move_url(&self, source: &Url, target: &Url) -> Result<bool> {
+        if source.scheme() != "file" || target.scheme() != "file" {
+            return Ok(false);
+        }
+        let (Ok(source), Ok(target)) = (source.to_file_path(), target.to_file_path()) else {
+            return Ok(false);
+        };
+        match rename(source, target) {
+            Ok(()) => Ok(true),
+            Err(error) if error.kind() == ErrorKind::CrossesDevices => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn walk_url_container(&self, url: &Url, options: &WalkOptions) -> Result<Vec<Url>> {
+        let Ok(path) = url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        let matcher = WalkMatcher::new(options)?;
+        let mut visited: HashSet<PathBuf> = HashSet::from([path.canonicalize()?]);
+        let mut entries: Vec<WalkEntry> = Vec::new();
+        walk_directory(&path, "", 1, &matcher, &mut visited, &mut entries)?;
+        Ok(matcher.select(entries))
+    }
+
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        let Ok(path) = url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        let bytes = read(path)?;
+        Ok(Some(bytes))
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        let Ok(path) = url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        let path = resolve_symlinks(&path)?;
+
+        let target_dir: &Path = path
+            .parent()
+            .context("Could not find target directory for {url}")?;
+        if !target_dir.exists() {
+            create_dir_all(target_dir)?;
+        }
+
+        let file_name = path
+            .file_name()
+            .context("Could not find file name for {url}")?
+            .to_string_lossy();
+        let prefix = format!(".{file_name}.");
+        let mut builder = Builder::new();
+        builder.prefix(&prefix).suffix(".tmp");
+        #[cfg(unix)]
+        builder.permissions(default_permissions());
+        let mut tmp_file = builder.tempfile_in(target_dir)?;
+
+        tmp_file.write_all(bytes)?;
+        if let Ok(existing) = metadata(&path) {
+            tmp_file.as_file().set_permissions(existing.permissions())?;
+        }
+        tmp_file.as_file().sync_all()?;
+        tmp_file.persist(&path)?;
+
+        if self.sync_parent_directory {
+            File::open(target_dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+    fn update_bytes_at_url(
+        &self,
+        url: &Url,
+        update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let Ok(path) = url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        let lock_file = lock_path(&path)?;
+
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            anyhow::bail!("Record at target location empty!");
+        };
+        let bytes = update(bytes)?;
+        self.push_bytes_to_url(url, &bytes)?;
+
+        lock_file.unlock()?;
+        Ok(())
+    }
+    fn fetch_bytes_with_version(&self, url: &Url) -> Result<(Option<Vec<u8>>, Option<UrlVersion>)> {
+        let Ok(path) = url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        let bytes = match read(path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok((None, None)),
+            Err(error) => return Err(error.into()),
+        };
+        let version = UrlVersion::from_content(&bytes);
+        Ok((Some(bytes), Some(version)))
+    }
+    fn push_bytes_to_url_if_version(
+        &self,
+        url: &Url,
+        bytes: &[u8],
+        expected: Option<&UrlVersion>,
+    ) -> Result<()> {
+        let Ok(path) = url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        if let Some(target_dir) = path.parent() {
+            create_dir_all(target_dir)?;
+        }
+        let lock_file = lock_path(&path)?;
+
+        let found = match read(&path) {
+            Ok(bytes) => Some(UrlVersion::from_content(&bytes)),
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(error) => return Err(error.into()),
+        };
+        if found.as_ref() != expected {
+            return Err(ConflictError::new(url, expected, found.as_ref()).into());
+        }
+        self.push_bytes_to_url(url, bytes)?;
+
+        lock_file.unlock()?;
+        Ok(())
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        let Ok(path) = url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        let metadata = match metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(UrlMetadata::missing()),
+            Err(error) => return Err(error.into()),
+        };
+        Ok(UrlMetadata {
+            exists: true,
+            is_container: metadata.is_dir(),
+            size: Some(metadata.len()),
+            modified: Some(DateTime::<Utc>::from(metadata.modified()?)),
+            version: match metadata.is_file() {
+                true => Some(UrlVersion::from_content(&read(&path)?)),
+                false => None,
+            },
+            permissions: get_permissions(&metadata),
+        })
+    }
+}
+
+// Lock files live outside the record directories so they never show up in listings.
+// They are never removed, another process might already wait for the lock.
+fn lock_path(path: &Path) -> Result<File> {
+    let path = resolve_symlinks(&path.absolutize()?)?;
+    let lock_dir = dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("url_handler-locks");
+    create_dir_all(&lock_dir)?;
+    let digest = Sha256::digest(path.to_string_lossy().as_bytes());
+    let name: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    let lock_file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_dir.join(format!("{name}.lock")))?;
+    lock_file.lock()?;
+    Ok(lock_file)
+}
+
+fn is_protected_path(path: &Path) -> Result<bool> {
+    let path = path.canonicalize()?;
+    let home = dirs::home_dir().and_then(|home| home.canonicalize().ok());
+    Ok(path.parent().is_none() || Some(&path) == home.as_ref())
+}
+
+fn walk_directory(
+    path: &Path,
+    parent: &str,
+    depth: usize,
+    matcher: &WalkMatcher,
+    visited: &mut HashSet<PathBuf>,
+    entries: &mut Vec<WalkEntry>,
+) -> Result<()> {
+    for dir_entry in path.read_dir()? {
+        let Ok(dir_entry) = dir_entry else {
+            continue;
+        };
+        let name = dir_entry.file_name().to_string_lossy().to_string();
+        let entry_path = match parent.is_empty() {
+            true => name,
+            false => format!("{parent}/{name}"),
+        };
+        if !matcher.accepts_path(&entry_path) {
+            continue;
+        }
+        let file_type = dir_entry.file_type()?;
+        let is_container = match file_type.is_symlink() && matcher.follow_symlinks() {
+            true => metadata(dir_entry.path()).is_ok_and(|metadata| metadata.is_dir()),
+            false => file_type.is_dir(),
+        };
+        // Canonical paths guard against symlink loops when following links.
+        if is_container
+            && matcher.should_descend(depth)
+            && visited.insert(dir_entry.path().canonicalize()?)
+        {
+            walk_directory(
+                &dir_entry.path(),
+                &entry_path,
+                depth + 1,
+                matcher,
+                visited,
+                entries,
+            )?;
+        }
+        let Ok(url) = try_build_url_from_path_buf(&dir_entry.path()) else {
+            continue;
+        };
+        entries.push(WalkEntry {
+            url,
+            path: entry_path,
+            is_container,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn get_permissions(metadata: &Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn get_permissions(_: &Metadata) -> Option<u32> {
+    None
 }
 
 pub fn try_build_url_from_path_buf(path: &PathBuf) -> Result<Url> {
@@ -0,0 +1,212 @@
+use super::walk::build_child_url;
+use super::{FileProtocolHandler, ProtocolHandler, UrlMetadata};
+use crate::external_fascade::{GitAuthor, GitFascade};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use url::Url;
+
+const DEFAULT_MESSAGE: &str = "{action} {path}";
+const DEFAULT_REMOTE: &str = "origin";
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GitProtocolHandlerConfig {
+    author_name: Option<String>,
+    author_email: Option<String>,
+    message: Option<String>,
+    #[serde(default)]
+    push: bool,
+    remote: Option<String>,
+    branch: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct GitUrl {
+    file_url: Url,
+    repository: PathBuf,
+    path: String,
+    revision: Option<String>,
+}
+
+impl GitUrl {
+    fn try_from_url(url: &Url) -> Result<Self> {
+        let Some(file_url) = url.as_str().strip_prefix("git+") else {
+            anyhow::bail!("URL '{url}' is not a git URL");
+        };
+        let mut file_url = Url::parse(file_url)?;
+        let revision = file_url
+            .query_pairs()
+            .find(|(key, _)| key == "ref")
+            .map(|(_, value)| value.to_string())
+            .or_else(|| file_url.fragment().map(String::from));
+        file_url.set_query(None);
+        file_url.set_fragment(None);
+
+        let Ok(path) = file_url.to_file_path() else {
+            anyhow::bail!("Could not parse URL to path");
+        };
+        let repository = GitFascade::get_toplevel(&path)?;
+        // Missing directories are created by the file handler, so only the existing part is resolved.
+        let Some(parent) = path.parent() else {
+            anyhow::bail!("Could not find parent directory of '{}'", path.display());
+        };
+        let Some(existing) = parent.ancestors().find(|ancestor| ancestor.exists()) else {
+            anyhow::bail!("Could not find parent directory of '{}'", path.display());
+        };
+        let parent = existing.canonicalize()?.join(parent.strip_prefix(existing)?);
+        let path = match path.file_name() {
+            Some(name) => parent.join(name),
+            None => parent,
+        };
+        let Ok(path) = path.strip_prefix(&repository) else {
+            anyhow::bail!("'{}' is outside of the repository", path.display());
+        };
+
+        Ok(GitUrl {
+            file_url,
+            repository,
+            path: path.to_string_lossy().to_string(),
+            revision,
+        })
+    }
+
+    fn ensure_working_tree(&self) -> Result<()> {
+        if let Some(revision) = &self.revision {
+            anyhow::bail!("Can not modify the repository at revision '{revision}'");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct GitProtocolHandler {
+    config: GitProtocolHandlerConfig,
+    file_handler: FileProtocolHandler,
+}
+
+impl GitProtocolHandler {
+    pub fn new(config: &GitProtocolHandlerConfig, file_handler: FileProtocolHandler) -> Self {
+        GitProtocolHandler {
+            config: config.clone(),
+            file_handler,
+        }
+    }
+
+    fn commit(&self, git_url: &GitUrl, action: &str) -> Result<()> {
+        let message = self
+            .config
+            .message
+            .as_deref()
+            .unwrap_or(DEFAULT_MESSAGE)
+            .replace("{action}", action)
+            .replace("{path}", &git_url.path)
+            .replace("{url}", git_url.file_url.as_str());
+        let author = match (&self.config.author_name, &self.config.author_email) {
+            (Some(name), Some(email)) => Some(GitAuthor { name, email }),
+            _ => None,
+        };
+        let path = match git_url.path.as_str() {
+            "" => ".",
+            path => path,
+        };
+        let has_committed =
+            GitFascade::commit_path(&git_url.repository, path, &message, author.as_ref())?;
+        if has_committed && self.config.push {
+            GitFascade::push(
+                &git_url.repository,
+                self.config.remote.as_deref().unwrap_or(DEFAULT_REMOTE),
+                self.config.branch.as_deref(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl ProtocolHandler for GitProtocolHandler {
+    fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            return Ok(None);
+        };
+        Ok(Some(String::from_utf8(bytes)?))
+    }
+    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+        self.push_bytes_to_url(url, string.as_bytes())
+    }
+    fn delete_string_from_url(&self, url: &Url) -> Result<()> {
+        let git_url = GitUrl::try_from_url(url)?;
+        git_url.ensure_working_tree()?;
+        self.file_handler
+            .delete_string_from_url(&git_url.file_url)?;
+        self.commit(&git_url, "Delete")
+    }
+    fn create_empty_string_on_url(&self, url: &Url) -> Result<()> {
+        self.push_bytes_to_url(url, &[])
+    }
+    fn create_url_container(&self, url: &Url) -> Result<()> {
+        let git_url = GitUrl::try_from_url(url)?;
+        git_url.ensure_working_tree()?;
+        self.file_handler.create_url_container(&git_url.file_url)
+    }
+    fn list_urls_in_url_container(&self, url: &Url) -> Result<HashSet<Url>> {
+        let git_url = GitUrl::try_from_url(url)?;
+        let mut urls: HashSet<Url> = HashSet::default();
+        match &git_url.revision {
+            Some(revision) => {
+                let entries = GitFascade::list_tree(&git_url.repository, revision, &git_url.path)?;
+                for (path, is_tree) in entries {
+                    let name = path.rsplit('/').next().unwrap_or_default();
+                    let child = match is_tree {
+                        true => build_child_url(url, &format!("{name}/"))?,
+                        false => build_child_url(url, name)?,
+                    };
+                    urls.insert(child);
+                }
+            }
+            None => {
+                for child in self
+                    .file_handler
+                    .list_urls_in_url_container(&git_url.file_url)?
+                {
+                    if child.path().ends_with("/.git") {
+                        continue;
+                    }
+                    urls.insert(Url::parse(&format!("git+{child}"))?);
+                }
+            }
+        }
+        Ok(urls)
+    }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        let git_url = GitUrl::try_from_url(url)?;
+        match &git_url.revision {
+            Some(revision) => Ok(Some(GitFascade::show(
+                &git_url.repository,
+                revision,
+                &git_url.path,
+            )?)),
+            None => self.file_handler.fetch_bytes_from_url(&git_url.file_url),
+        }
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        let git_url = GitUrl::try_from_url(url)?;
+        git_url.ensure_working_tree()?;
+        self.file_handler
+            .push_bytes_to_url(&git_url.file_url, bytes)?;
+        self.commit(&git_url, "Update")
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        let git_url = GitUrl::try_from_url(url)?;
+        if let Some(revision) = &git_url.revision {
+            anyhow::bail!("Stat is not supported at revision '{revision}'");
+        }
+        self.file_handler.stat(&git_url.file_url)
+    }
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        let git_url = GitUrl::try_from_url(url)?;
+        git_url.ensure_working_tree()?;
+        self.file_handler
+            .delete_url_container(&git_url.file_url, recursive)?;
+        self.commit(&git_url, "Delete")
+    }
+}
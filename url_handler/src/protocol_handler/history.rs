@@ -0,0 +1,135 @@
+use super::walk::{build_child_url, get_entry_name, get_parent_url};
+use super::{
+    create_url_container, fetch_bytes_from_url, move_url, push_bytes_to_url, stat_url, url_exists,
+    ProtocolHandlerRegistry,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+const DEFAULT_HISTORY_NAME: &str = ".history";
+const DEFAULT_KEEP: usize = 5;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct HistoryConfig {
+    #[serde(default)]
+    enabled: bool,
+    keep: Option<usize>,
+    name: Option<String>,
+    prefixes: Option<Vec<String>>,
+}
+
+impl HistoryConfig {
+    fn get_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(DEFAULT_HISTORY_NAME)
+    }
+
+    fn get_keep(&self) -> usize {
+        self.keep.unwrap_or(DEFAULT_KEEP)
+    }
+
+    pub fn should_keep_history(&self, url: &Url) -> bool {
+        let is_in_history = url
+            .path_segments()
+            .is_some_and(|mut segments| segments.any(|segment| segment == self.get_name()));
+        let is_covered = match &self.prefixes {
+            None => true,
+            Some(prefixes) => prefixes
+                .iter()
+                .any(|prefix| url.as_str().starts_with(prefix.as_str())),
+        };
+        self.enabled && self.get_keep() > 0 && is_covered && !is_in_history
+    }
+
+    pub fn get_history_container(&self, url: &Url) -> Result<Url> {
+        build_child_url(&get_parent_url(url)?, self.get_name())
+    }
+
+    pub fn get_version_url(&self, url: &Url, number: usize) -> Result<Url> {
+        let name = format!("{}.~{number}~", get_entry_name(url)?);
+        build_child_url(&self.get_history_container(url)?, &name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub number: usize,
+    pub url: Url,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+// The previous content is read before a push and only kept once the push succeeded.
+pub(crate) fn snapshot(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<Option<Vec<u8>>> {
+    let metadata = stat_url(url, registry)
+        .with_context(|| format!("Could not keep history for '{url}' as it could not be stat'ed"))?;
+    if !metadata.exists || metadata.is_container {
+        return Ok(None);
+    }
+    fetch_bytes_from_url(url, registry)
+}
+
+// Versions are rotated so that `~1~` always holds the content before the latest push.
+pub(crate) fn keep_history(
+    url: &Url,
+    previous: Option<Vec<u8>>,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<()> {
+    let Some(previous) = previous else {
+        return Ok(());
+    };
+    let config = registry.get_history_config();
+    let history = config.get_history_container(url)?;
+    if !url_exists(&history, registry)? {
+        create_url_container(&history, registry)?;
+    }
+    for number in (1..config.get_keep()).rev() {
+        let version = config.get_version_url(url, number)?;
+        if url_exists(&version, registry)? {
+            move_url(
+                &version,
+                &config.get_version_url(url, number + 1)?,
+                registry,
+            )?;
+        }
+    }
+    push_bytes_to_url(&config.get_version_url(url, 1)?, &previous, registry)
+}
+
+pub fn list_versions(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<Vec<HistoryEntry>> {
+    let config = registry.get_history_config();
+    let mut versions = Vec::new();
+    for number in 1..=config.get_keep() {
+        let version = config.get_version_url(url, number)?;
+        let metadata = stat_url(&version, registry)?;
+        if !metadata.exists {
+            break;
+        }
+        versions.push(HistoryEntry {
+            number,
+            url: version,
+            modified: metadata.modified,
+        });
+    }
+    Ok(versions)
+}
+
+pub fn fetch_version(
+    url: &Url,
+    number: usize,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<Option<Vec<u8>>> {
+    let version = registry.get_history_config().get_version_url(url, number)?;
+    if !url_exists(&version, registry)? {
+        anyhow::bail!("Version {number} of '{url}' does not exist!");
+    }
+    fetch_bytes_from_url(&version, registry)
+}
+
+// Restores a version with a regular push, so the replaced content becomes `~1~` in turn.
+pub fn rollback(url: &Url, number: usize, registry: &ProtocolHandlerRegistry) -> Result<()> {
+    let Some(bytes) = fetch_version(url, number, registry)? else {
+        anyhow::bail!("Version {number} of '{url}' is empty!");
+    };
+    push_bytes_to_url(url, &bytes, registry)
+}
@@ -1,14 +1,116 @@
-use super::ProtocolHandler;
+use super::{ConflictError, ProtocolHandler, UrlMetadata, UrlVersion};
 use std::collections::HashSet;
 use anyhow::Result;
+use path_absolutize::Absolutize;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 use reqwest::{
     blocking::{Client, RequestBuilder},
-    header::HeaderMap,
+    header::{
+        HeaderMap, CONTENT_LENGTH, ETAG, IF_MATCH, IF_NONE_MATCH, IF_UNMODIFIED_SINCE,
+        LAST_MODIFIED,
+    },
+    Method, StatusCode,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use url::Url;
 
+const UNIX_SCHEME: &str = "http+unix";
+const UNIX_HOST_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b':')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'@')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'|');
+const MAX_UPDATE_ATTEMPTS: u32 = 10;
+const UPDATE_BACKOFF: Duration = Duration::from_millis(10);
+
+pub(crate) fn backoff_after_conflict(attempt: u32) {
+    let delay = UPDATE_BACKOFF * 2u32.pow(attempt.min(6));
+    let jitter = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.subsec_nanos())
+        % delay.as_millis().max(1) as u32;
+    thread::sleep(delay / 2 + Duration::from_millis(jitter.into()));
+}
+
+pub fn try_build_url_from_unix_socket(socket: &PathBuf, path: &str) -> Result<Url> {
+    let socket = socket.absolutize()?;
+    let Some(socket) = socket.to_str() else {
+        anyhow::bail!("Socket path '{}' is not valid UTF-8", socket.display());
+    };
+    let host = utf8_percent_encode(socket, UNIX_HOST_ENCODE_SET);
+    let path = path.strip_prefix('/').unwrap_or(path);
+    Ok(Url::parse(&format!("{UNIX_SCHEME}://{host}/{path}"))?)
+}
+
+fn get_unix_socket_path(url: &Url) -> Result<PathBuf> {
+    let Some(host) = url.host_str() else {
+        anyhow::bail!("Could not extract socket path from url");
+    };
+    Ok(PathBuf::from(percent_decode_str(host).decode_utf8()?.as_ref()))
+}
+
+// The request line and Host header still need a plain http URL when talking over a socket.
+fn to_http_url(url: &Url) -> Result<Url> {
+    if url.scheme() != UNIX_SCHEME {
+        return Ok(url.clone());
+    }
+    let mut http_url = Url::parse("http://localhost/")?;
+    http_url.set_path(url.path());
+    http_url.set_query(url.query());
+    Ok(http_url)
+}
+
+#[cfg(unix)]
+fn build_client(url: &Url) -> Result<Client> {
+    match url.scheme() {
+        UNIX_SCHEME => Ok(Client::builder()
+            .unix_socket(get_unix_socket_path(url)?)
+            .build()?),
+        _ => Ok(Client::new()),
+    }
+}
+
+#[cfg(not(unix))]
+fn build_client(url: &Url) -> Result<Client> {
+    match url.scheme() {
+        UNIX_SCHEME => anyhow::bail!("Unix sockets are not supported on this platform!"),
+        _ => Ok(Client::new()),
+    }
+}
+
+fn parse_last_modified(headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    headers
+        .get(LAST_MODIFIED)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .map(|modified| modified.with_timezone(&Utc))
+}
+
+fn get_host_key(url: &Url) -> Result<String> {
+    if url.scheme() == UNIX_SCHEME {
+        return Ok(get_unix_socket_path(url)?.to_string_lossy().to_string());
+    }
+    let Some(host) = url.host() else {
+        anyhow::bail!("Could not extract host from url");
+    };
+    Ok(host.to_string())
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
 pub struct HttpProtocolHandlerConfig {
     hosts: Option<HashMap<String, HostConfig>>,
@@ -22,11 +124,15 @@ pub enum HttpMethod {
     Patch,
     Post,
     Put,
+    Copy,
+    Move,
+    Mkcol,
 }
 
 impl HttpMethod {
-    pub fn to_request(&self, url: &Url) -> RequestBuilder {
-        let client = Client::new();
+    pub fn to_request(&self, url: &Url) -> Result<RequestBuilder> {
+        let client = build_client(url)?;
+        let url = &to_http_url(url)?;
         let request = match self {
             HttpMethod::Delete => client.delete(url.as_str()),
             HttpMethod::Get => client.get(url.as_str()),
@@ -34,8 +140,11 @@ impl HttpMethod {
             HttpMethod::Patch => client.patch(url.as_str()),
             HttpMethod::Post => client.post(url.as_str()),
             HttpMethod::Put => client.put(url.as_str()),
+            HttpMethod::Copy => client.request(Method::from_bytes(b"COPY")?, url.as_str()),
+            HttpMethod::Move => client.request(Method::from_bytes(b"MOVE")?, url.as_str()),
+            HttpMethod::Mkcol => client.request(Method::from_bytes(b"MKCOL")?, url.as_str()),
         };
-        request
+        Ok(request)
     }
 }
 
@@ -64,23 +173,37 @@ impl HttpProtocolHandler {
         }
     }
 
-    fn build_request_with_config(
+    pub(crate) fn build_request_with_config(
         &self,
         url: &Url,
         default_method: HttpMethod,
     ) -> Result<RequestBuilder> {
-        let Some(host) = url.host() else {
-            anyhow::bail!("Could not extract host from url");
+        let Some(config) = self.config_per_host.get(&get_host_key(url)?) else {
+            return default_method.to_request(url);
         };
-        let Some(config) = self.config_per_host.get(&format!("{}", host)) else {
-            return Ok(default_method.to_request(url));
+
+        let configured_method = match default_method {
+            HttpMethod::Get => &config.fetch_method,
+            HttpMethod::Put => &config.push_method,
+            _ => &None,
         };
+        let method = match configured_method {
+            Some(method) => method.clone(),
+            None => default_method,
+        };
+        self.build_request_with_method(url, method)
+    }
 
-        let method = match &config.fetch_method {
-            Some(method) => method,
-            None => &default_method,
+    // Applies auth and headers of the host but ignores its fetch and push methods.
+    pub(crate) fn build_request_with_method(
+        &self,
+        url: &Url,
+        method: HttpMethod,
+    ) -> Result<RequestBuilder> {
+        let request = method.to_request(url)?;
+        let Some(config) = self.config_per_host.get(&get_host_key(url)?) else {
+            return Ok(request);
         };
-        let request = method.to_request(url);
 
         let request = match &config.user {
             Some(user) => request.basic_auth(user, config.password.clone()),
@@ -100,6 +223,29 @@ impl HttpProtocolHandler {
 
         Ok(request)
     }
+
+    fn try_webdav_transfer(&self, source: &Url, target: &Url, method: HttpMethod) -> Result<bool> {
+        let is_same_origin = match source.scheme() {
+            UNIX_SCHEME => target.scheme() == UNIX_SCHEME && source.host_str() == target.host_str(),
+            _ => matches!(target.scheme(), "http" | "https") && source.origin() == target.origin(),
+        };
+        if !is_same_origin {
+            return Ok(false);
+        }
+        let response = self
+            .build_request_with_config(source, method)?
+            .header("Destination", to_http_url(target)?.as_str())
+            .header("Overwrite", "T")
+            .send()?;
+        if matches!(
+            response.status(),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+        ) {
+            return Ok(false);
+        }
+        response.error_for_status()?;
+        Ok(true)
+    }
 }
 
 impl ProtocolHandler for HttpProtocolHandler {
@@ -131,10 +277,152 @@ impl ProtocolHandler for HttpProtocolHandler {
             .error_for_status()?;
         Ok(())
     }
-    fn create_url_container(&self, _: &Url) -> Result<()> {
-        todo!("Create Container Container Operation is not yet implemented for the http handler!")
+    fn create_url_container(&self, url: &Url) -> Result<()> {
+        let response = self
+            .build_request_with_config(url, HttpMethod::Mkcol)?
+            .send()?;
+        // Existing collections and servers without WebDAV, which create paths on PUT, refuse MKCOL.
+        if matches!(
+            response.status(),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+        ) {
+            return Ok(());
+        }
+        response.error_for_status()?;
+        Ok(())
     }
     fn list_urls_in_url_container(&self, _: &Url) -> Result<HashSet<Url>> {
-        todo!("List URL Operation is not yet implemented for the http handler!")
+        anyhow::bail!("Listing containers is not supported by the http handler!")
+    }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        let request = self.build_request_with_config(url, HttpMethod::Get)?;
+        let response = request.send()?.error_for_status()?;
+        let bytes = response.bytes()?;
+        Ok(Some(bytes.to_vec()))
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        self.build_request_with_config(url, HttpMethod::Put)?
+            .body(bytes.to_vec())
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+    fn update_bytes_at_url(
+        &self,
+        url: &Url,
+        update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        for attempt in 0..MAX_UPDATE_ATTEMPTS {
+            let (Some(bytes), version) = self.fetch_bytes_with_version(url)? else {
+                anyhow::bail!("Record at target location empty!");
+            };
+            let Some(version) = version else {
+                anyhow::bail!("'{url}' has no ETag or Last-Modified date to guard the update");
+            };
+            let bytes = update(bytes)?;
+            match self.push_bytes_to_url_if_version(url, &bytes, Some(&version)) {
+                Err(error) if error.is::<ConflictError>() => backoff_after_conflict(attempt),
+                result => return result,
+            }
+        }
+        anyhow::bail!("Could not update '{url}' because it kept changing concurrently!")
+    }
+    fn fetch_bytes_with_version(&self, url: &Url) -> Result<(Option<Vec<u8>>, Option<UrlVersion>)> {
+        let response = self
+            .build_request_with_config(url, HttpMethod::Get)?
+            .send()?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok((None, None));
+        }
+        let response = response.error_for_status()?;
+        let etag = response.headers().get(ETAG).cloned();
+        let modified = parse_last_modified(response.headers());
+        let bytes = response.bytes()?.to_vec();
+        let version = match (etag, modified) {
+            (Some(etag), _) => Some(UrlVersion::ETag(etag.to_str()?.to_string())),
+            (None, Some(modified)) => Some(UrlVersion::Modified {
+                modified,
+                size: bytes.len() as u64,
+            }),
+            (None, None) => None,
+        };
+        Ok((Some(bytes), version))
+    }
+    fn push_bytes_to_url_if_version(
+        &self,
+        url: &Url,
+        bytes: &[u8],
+        expected: Option<&UrlVersion>,
+    ) -> Result<()> {
+        let request = self
+            .build_request_with_config(url, HttpMethod::Put)?
+            .body(bytes.to_vec());
+        let request = match expected {
+            Some(UrlVersion::ETag(etag)) => request.header(IF_MATCH, etag),
+            Some(UrlVersion::Modified { modified, .. }) => request.header(
+                IF_UNMODIFIED_SINCE,
+                modified.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+            ),
+            Some(version) => anyhow::bail!("HTTP can only compare ETags and dates, not {version:?}"),
+            None => request.header(IF_NONE_MATCH, "*"),
+        };
+        let response = request.send()?;
+        if response.status() == StatusCode::PRECONDITION_FAILED {
+            let (_, found) = self.fetch_bytes_with_version(url)?;
+            return Err(ConflictError::new(url, expected, found.as_ref()).into());
+        }
+        response.error_for_status()?;
+        Ok(())
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        let response = self
+            .build_request_with_config(url, HttpMethod::Head)?
+            .send()?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(UrlMetadata::missing());
+        }
+        let response = response.error_for_status()?;
+        let headers = response.headers();
+        Ok(UrlMetadata {
+            exists: true,
+            is_container: url.path().ends_with('/'),
+            size: headers
+                .get(CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok()),
+            modified: parse_last_modified(headers),
+            version: headers
+                .get(ETAG)
+                .and_then(|value| value.to_str().ok())
+                .map(|etag| UrlVersion::ETag(etag.to_string())),
+            permissions: None,
+        })
+    }
+    fn ensure_container_can_be_deleted(&self, url: &Url, recursive: bool) -> Result<()> {
+        if !recursive {
+            anyhow::bail!("WebDAV collections can only be deleted recursively!");
+        }
+        if url.path().trim_end_matches('/').is_empty() {
+            anyhow::bail!("Refusing to delete the root collection of '{url}'");
+        }
+        Ok(())
+    }
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        self.ensure_container_can_be_deleted(url, recursive)?;
+        let mut url = url.clone();
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        self.build_request_with_config(&url, HttpMethod::Delete)?
+            .header("Depth", "infinity")
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+    fn try_copy_url(&self, source: &Url, target: &Url) -> Result<bool> {
+        self.try_webdav_transfer(source, target, HttpMethod::Copy)
+    }
+    fn try_move_url(&self, source: &Url, target: &Url) -> Result<bool> {
+        self.try_webdav_transfer(source, target, HttpMethod::Move)
     }
 }
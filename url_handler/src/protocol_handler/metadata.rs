@@ -0,0 +1,19 @@
+use super::UrlVersion;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct UrlMetadata {
+    pub exists: bool,
+    pub is_container: bool,
+    pub size: Option<u64>,
+    pub modified: Option<DateTime<Utc>>,
+    pub version: Option<UrlVersion>,
+    pub permissions: Option<u32>,
+}
+
+impl UrlMetadata {
+    pub fn missing() -> Self {
+        UrlMetadata::default()
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use url::Url;
+
+// Named, ordered lists of URLs that hold the same record.
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct MirrorGroups(HashMap<String, Vec<Url>>);
+
+impl MirrorGroups {
+    pub fn get(&self, group: &str) -> Option<&[Url]> {
+        self.0.get(group).map(Vec::as_slice)
+    }
+}
+
+#[derive(Debug)]
+pub struct MirrorError {
+    errors: Vec<(Url, anyhow::Error)>,
+}
+
+impl MirrorError {
+    pub fn new(errors: Vec<(Url, anyhow::Error)>) -> Self {
+        MirrorError { errors }
+    }
+
+    pub fn errors(&self) -> &[(Url, anyhow::Error)] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for MirrorError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "All {} mirrors failed", self.errors.len())?;
+        for (url, error) in &self.errors {
+            write!(formatter, "\n  {url}: {error:#}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MirrorError {}
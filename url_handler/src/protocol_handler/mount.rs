@@ -0,0 +1,66 @@
+use super::registry::is_registered_scheme;
+use super::walk::build_child_url;
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use url::Url;
+
+// Maps alias schemes like `prod` to the base URL that `prod:/app.toml` is resolved against.
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct MountTable(#[serde(deserialize_with = "deserialize_mounts")] HashMap<String, Url>);
+
+// Aliases named like a registered scheme would shadow its handler.
+fn deserialize_mounts<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, Url>, D::Error> {
+    let mounts = HashMap::<String, Url>::deserialize(deserializer)?;
+    if let Some(name) = mounts.keys().find(|name| is_registered_scheme(name)) {
+        return Err(D::Error::custom(format!(
+            "Mount '{name}' collides with the registered '{name}' scheme"
+        )));
+    }
+    Ok(mounts)
+}
+
+fn as_container(base: &Url) -> String {
+    match base.as_str().ends_with('/') {
+        true => base.to_string(),
+        false => format!("{base}/"),
+    }
+}
+
+impl MountTable {
+    pub fn resolve(&self, url: &Url) -> Result<Url> {
+        let Some(base) = self.0.get(url.scheme()) else {
+            return Ok(url.clone());
+        };
+        let path = percent_decode_str(url.path()).decode_utf8()?;
+        let mut target = match path.trim_start_matches('/') {
+            "" => base.clone(),
+            path => build_child_url(base, path)?,
+        };
+        if url.query().is_some() {
+            target.set_query(url.query());
+        }
+        if url.fragment().is_some() {
+            target.set_fragment(url.fragment());
+        }
+        Ok(target)
+    }
+
+    // Maps a URL below the mount of `input` back to the alias, other URLs are returned unchanged.
+    pub fn to_alias(&self, input: &Url, url: &Url) -> Url {
+        let alias = input.scheme();
+        let Some(base) = self.0.get(alias).map(as_container) else {
+            return url.clone();
+        };
+        if !url.as_str().starts_with(base.as_str()) && as_container(url) != base {
+            return url.clone();
+        }
+        let rest = url.as_str().get(base.len()..).unwrap_or_default();
+        Url::parse(&format!("{alias}:/{rest}")).unwrap_or_else(|_| url.clone())
+    }
+}
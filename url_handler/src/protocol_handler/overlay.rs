@@ -0,0 +1,275 @@
+use super::registry::{ProtocolHandlerConfig, ProtocolHandlerRegistry};
+use super::walk::{build_child_url, get_entry_name};
+use super::{
+    create_url_container, delete_string_from_url, delete_url_container, fetch_bytes_from_url,
+    list_urls_in_url_container, push_bytes_to_url, stat_url, update_bytes_at_url, ProtocolHandler,
+    UrlMetadata,
+};
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use url::Url;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "OverlayStacks")]
+pub struct OverlayProtocolHandlerConfig {
+    stacks: Option<HashMap<String, OverlayStackConfig>>,
+}
+
+#[derive(Deserialize)]
+struct OverlayStacks {
+    stacks: Option<HashMap<String, OverlayStackConfig>>,
+}
+
+// Stacks that layer each other in a cycle are rejected when the config is loaded.
+impl TryFrom<OverlayStacks> for OverlayProtocolHandlerConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(overlay: OverlayStacks) -> Result<Self> {
+        let stacks = overlay.stacks.unwrap_or_default();
+        let mut names: Vec<&String> = stacks.keys().collect();
+        names.sort();
+        for name in names {
+            if let Some(cycle) = find_cycle(&stacks, &mut vec![name.as_str()]) {
+                anyhow::bail!("Overlay stacks form a cycle: {}", cycle.join(" -> "));
+            }
+        }
+        Ok(OverlayProtocolHandlerConfig {
+            stacks: Some(stacks),
+        })
+    }
+}
+
+fn find_cycle<'a>(
+    stacks: &'a HashMap<String, OverlayStackConfig>,
+    path: &mut Vec<&'a str>,
+) -> Option<Vec<&'a str>> {
+    let stack = stacks.get(*path.last()?)?;
+    let references = std::iter::once(&stack.top)
+        .chain(&stack.layers)
+        .filter(|layer| layer.scheme() == "overlay")
+        .filter_map(|layer| layer.host_str());
+    for reference in references {
+        if path.contains(&reference) {
+            return Some([path.as_slice(), &[reference]].concat());
+        }
+        path.push(reference);
+        if let Some(cycle) = find_cycle(stacks, path) {
+            return Some(cycle);
+        }
+        path.pop();
+    }
+    None
+}
+
+// Reads go through `top` first and then through `layers` in order, writes only go to `top`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+struct OverlayStackConfig {
+    top: Url,
+    #[serde(default)]
+    layers: Vec<Url>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct OverlayUrl {
+    top: Url,
+    layers: Vec<Url>,
+}
+
+#[derive(Clone, Debug)]
+pub struct OverlayProtocolHandler {
+    stacks: HashMap<String, OverlayStackConfig>,
+    config: Box<ProtocolHandlerConfig>,
+    registry: OnceLock<Box<ProtocolHandlerRegistry>>,
+}
+
+impl OverlayProtocolHandler {
+    pub fn new(overlay: &OverlayProtocolHandlerConfig, config: &ProtocolHandlerConfig) -> Self {
+        OverlayProtocolHandler {
+            stacks: match &overlay.stacks {
+                None => HashMap::default(),
+                Some(map) => map.clone(),
+            },
+            config: Box::new(config.clone()),
+            registry: OnceLock::new(),
+        }
+    }
+
+    fn registry(&self) -> &ProtocolHandlerRegistry {
+        self.registry
+            .get_or_init(|| Box::new(ProtocolHandlerRegistry::new(&self.config)))
+    }
+
+    fn resolve(&self, url: &Url) -> Result<OverlayUrl> {
+        let Some(name) = url.host_str() else {
+            anyhow::bail!("Could not extract overlay name from '{url}'");
+        };
+        let Some(stack) = self.stacks.get(name) else {
+            anyhow::bail!("Overlay '{name}' is not configured in the ProtocolHandlerConfig!");
+        };
+
+        let path = percent_decode_str(url.path()).decode_utf8()?;
+        let path = path.trim_start_matches('/');
+        let build_layer_url = |base: &Url| match path {
+            "" => Ok(base.clone()),
+            path => build_child_url(base, path),
+        };
+        Ok(OverlayUrl {
+            top: build_layer_url(&stack.top)?,
+            layers: stack
+                .layers
+                .iter()
+                .map(build_layer_url)
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    // Returns the first layer that has the path together with its metadata.
+    // Unreachable layers are skipped, their error is only returned if no other layer has the path.
+    fn find_layer(&self, overlay_url: &OverlayUrl) -> Result<Option<(Url, UrlMetadata)>> {
+        let mut first_error = None;
+        for layer_url in std::iter::once(&overlay_url.top).chain(&overlay_url.layers) {
+            match stat_url(layer_url, self.registry()) {
+                Ok(metadata) if metadata.exists => {
+                    return Ok(Some((layer_url.clone(), metadata)));
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    log::warn!("Skipping overlay layer '{layer_url}': {error}");
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(None),
+        }
+    }
+
+    // Returns the names in a layer with a trailing slash for containers, or None if it's missing.
+    fn list_layer(&self, layer_url: &Url) -> Result<Option<Vec<String>>> {
+        let registry = self.registry();
+        if !stat_url(layer_url, registry)?.exists {
+            return Ok(None);
+        }
+        let mut names = Vec::new();
+        for child in list_urls_in_url_container(layer_url, registry)? {
+            let name = get_entry_name(&child)?;
+            match stat_url(&child, registry)?.is_container {
+                true => names.push(format!("{name}/")),
+                false => names.push(name),
+            }
+        }
+        Ok(Some(names))
+    }
+}
+
+impl ProtocolHandler for OverlayProtocolHandler {
+    fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            return Ok(None);
+        };
+        Ok(Some(String::from_utf8(bytes)?))
+    }
+    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+        self.push_bytes_to_url(url, string.as_bytes())
+    }
+    fn delete_string_from_url(&self, url: &Url) -> Result<()> {
+        let overlay_url = self.resolve(url)?;
+        let registry = self.registry();
+        if !stat_url(&overlay_url.top, registry)?.exists {
+            anyhow::bail!("'{url}' can only be deleted from the top layer of the overlay");
+        }
+        delete_string_from_url(&overlay_url.top, registry)
+    }
+    fn create_empty_string_on_url(&self, url: &Url) -> Result<()> {
+        self.push_bytes_to_url(url, &[])
+    }
+    fn create_url_container(&self, url: &Url) -> Result<()> {
+        let overlay_url = self.resolve(url)?;
+        let registry = self.registry();
+        create_url_container(&overlay_url.top, registry)
+    }
+    fn list_urls_in_url_container(&self, url: &Url) -> Result<HashSet<Url>> {
+        let overlay_url = self.resolve(url)?;
+        let mut names: HashSet<String> = HashSet::default();
+        let mut is_found = false;
+        let mut first_error = None;
+        for layer_url in std::iter::once(&overlay_url.top).chain(&overlay_url.layers) {
+            match self.list_layer(layer_url) {
+                Ok(Some(layer_names)) => {
+                    is_found = true;
+                    names.extend(layer_names);
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    log::warn!("Skipping overlay layer '{layer_url}': {error}");
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        match first_error {
+            Some(error) if !is_found => return Err(error),
+            None if !is_found => {
+                anyhow::bail!("Could not find '{url}' in any layer of the overlay")
+            }
+            _ => {}
+        }
+
+        // The same entry may be listed as a container by one layer and without a slash by another.
+        let mut urls: HashSet<Url> = HashSet::default();
+        for name in &names {
+            if !name.ends_with('/') && names.contains(&format!("{name}/")) {
+                continue;
+            }
+            urls.insert(build_child_url(url, name)?);
+        }
+        Ok(urls)
+    }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        let overlay_url = self.resolve(url)?;
+        let registry = self.registry();
+        match self.find_layer(&overlay_url)? {
+            Some((layer_url, _)) => fetch_bytes_from_url(&layer_url, registry),
+            None => Ok(None),
+        }
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        let overlay_url = self.resolve(url)?;
+        let registry = self.registry();
+        push_bytes_to_url(&overlay_url.top, bytes, registry)
+    }
+    fn update_bytes_at_url(
+        &self,
+        url: &Url,
+        update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let overlay_url = self.resolve(url)?;
+        let registry = self.registry();
+        let Some((layer_url, _)) = self.find_layer(&overlay_url)? else {
+            anyhow::bail!("Record at target location empty!");
+        };
+        if layer_url == overlay_url.top {
+            return update_bytes_at_url(&overlay_url.top, update, registry);
+        }
+        // Records from lower layers are copied up to the top layer on their first update.
+        let Some(bytes) = fetch_bytes_from_url(&layer_url, registry)? else {
+            anyhow::bail!("Record at target location empty!");
+        };
+        push_bytes_to_url(&overlay_url.top, &update(bytes)?, registry)
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        let overlay_url = self.resolve(url)?;
+        match self.find_layer(&overlay_url)? {
+            Some((_, metadata)) => Ok(metadata),
+            None => Ok(UrlMetadata::missing()),
+        }
+    }
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        let overlay_url = self.resolve(url)?;
+        let registry = self.registry();
+        delete_url_container(&overlay_url.top, recursive, registry)
+    }
+}
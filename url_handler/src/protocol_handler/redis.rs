@@ -0,0 +1,293 @@
+use super::walk::build_child_url;
+use super::{ProtocolHandler, UrlMetadata};
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use redis::{Client, Connection, ConnectionAddr, ConnectionInfo, RedisConnectionInfo, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use url::Url;
+
+const DEFAULT_PORT: u16 = 6379;
+const MAX_UPDATE_ATTEMPTS: u32 = 10;
+const SCAN_COUNT: u32 = 100;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RedisProtocolHandlerConfig {
+    #[serde(default, with = "humantime_serde")]
+    ttl: Option<Duration>,
+    hosts: Option<HashMap<String, RedisHostConfig>>,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+struct RedisHostConfig {
+    user: Option<String>,
+    password: Option<String>,
+    #[serde(default, with = "humantime_serde")]
+    ttl: Option<Duration>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct RedisUrl {
+    host: String,
+    port: u16,
+    db: i64,
+    key: String,
+    ttl: Option<Duration>,
+}
+
+impl RedisUrl {
+    fn try_from_url(url: &Url) -> Result<Self> {
+        let Some(host) = url.host_str() else {
+            anyhow::bail!("Could not extract host from url");
+        };
+        let path = percent_decode_str(url.path()).decode_utf8()?;
+        let path = path.strip_prefix('/').unwrap_or(&path);
+        let (db, key) = path.split_once('/').unwrap_or((path, ""));
+        let Ok(db) = db.parse() else {
+            anyhow::bail!("Could not parse database number from '{url}'");
+        };
+        let ttl = url
+            .query_pairs()
+            .find(|(key, _)| key == "ttl")
+            .map(|(_, ttl)| humantime::parse_duration(&ttl))
+            .transpose()?;
+
+        Ok(RedisUrl {
+            host: host.to_string(),
+            port: url.port().unwrap_or(DEFAULT_PORT),
+            db,
+            key: key.to_string(),
+            ttl,
+        })
+    }
+
+    fn get_key(&self) -> Result<&str> {
+        if self.key.is_empty() || self.key.ends_with('/') {
+            anyhow::bail!("URL does not name a key");
+        }
+        Ok(&self.key)
+    }
+
+    fn container_prefix(&self) -> String {
+        match self.key.trim_end_matches('/') {
+            "" => String::new(),
+            key => format!("{key}/"),
+        }
+    }
+}
+
+fn escape_pattern(prefix: &str) -> String {
+    let mut pattern = String::new();
+    for character in prefix.chars() {
+        if matches!(character, '*' | '?' | '[' | ']' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(character);
+    }
+    pattern
+}
+
+fn scan_keys_with_prefix(connection: &mut Connection, prefix: &str) -> Result<Vec<String>> {
+    let pattern = format!("{}*", escape_pattern(prefix));
+    let mut keys = Vec::new();
+    let mut cursor = 0u64;
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query(connection)?;
+        keys.extend(batch);
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    keys.sort();
+    keys.dedup();
+    Ok(keys)
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct RedisProtocolHandler {
+    ttl: Option<Duration>,
+    config_per_host: HashMap<String, RedisHostConfig>,
+}
+
+impl RedisProtocolHandler {
+    pub fn new(config: &RedisProtocolHandlerConfig) -> Self {
+        RedisProtocolHandler {
+            ttl: config.ttl,
+            config_per_host: match &config.hosts {
+                None => HashMap::default(),
+                Some(map) => map.clone(),
+            },
+        }
+    }
+
+    fn connect(&self, url: &Url) -> Result<(RedisUrl, Connection)> {
+        let redis_url = RedisUrl::try_from_url(url)?;
+        let host_config = self.config_per_host.get(&redis_url.host);
+        let username = match url.username() {
+            "" => host_config.and_then(|config| config.user.clone()),
+            user => Some(percent_decode_str(user).decode_utf8()?.to_string()),
+        };
+        let password = match url.password() {
+            None => host_config.and_then(|config| config.password.clone()),
+            Some(password) => Some(percent_decode_str(password).decode_utf8()?.to_string()),
+        };
+
+        let info = ConnectionInfo {
+            addr: ConnectionAddr::Tcp(redis_url.host.clone(), redis_url.port),
+            redis: RedisConnectionInfo {
+                db: redis_url.db,
+                username,
+                password,
+                ..RedisConnectionInfo::default()
+            },
+        };
+        let connection = Client::open(info)?.get_connection()?;
+        Ok((redis_url, connection))
+    }
+
+    // The TTL from the URL wins over the host config, which wins over the handler default.
+    fn get_ttl(&self, redis_url: &RedisUrl) -> Option<Duration> {
+        redis_url.ttl.or_else(|| {
+            self.config_per_host
+                .get(&redis_url.host)
+                .and_then(|config| config.ttl)
+                .or(self.ttl)
+        })
+    }
+}
+
+impl ProtocolHandler for RedisProtocolHandler {
+    fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            return Ok(None);
+        };
+        Ok(Some(String::from_utf8(bytes)?))
+    }
+    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+        self.push_bytes_to_url(url, string.as_bytes())
+    }
+    fn delete_string_from_url(&self, url: &Url) -> Result<()> {
+        let (redis_url, mut connection) = self.connect(url)?;
+        let key = redis_url.get_key()?;
+        let deleted: u64 = redis::cmd("DEL").arg(key).query(&mut connection)?;
+        if deleted == 0 {
+            anyhow::bail!("Could not find key '{key}' in database {}", redis_url.db);
+        }
+        Ok(())
+    }
+    fn create_empty_string_on_url(&self, url: &Url) -> Result<()> {
+        self.push_bytes_to_url(url, &[])
+    }
+    fn create_url_container(&self, _: &Url) -> Result<()> {
+        // Containers are key prefixes and exist as soon as a key below them does.
+        Ok(())
+    }
+    fn list_urls_in_url_container(&self, url: &Url) -> Result<HashSet<Url>> {
+        let (redis_url, mut connection) = self.connect(url)?;
+        let prefix = redis_url.container_prefix();
+        let mut urls: HashSet<Url> = HashSet::default();
+        for key in scan_keys_with_prefix(&mut connection, &prefix)? {
+            let rest = &key[prefix.len()..];
+            let child = match rest.split_once('/') {
+                Some((container, _)) => format!("{container}/"),
+                None => rest.to_string(),
+            };
+            urls.insert(build_child_url(url, &child)?);
+        }
+        Ok(urls)
+    }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        let (redis_url, mut connection) = self.connect(url)?;
+        let bytes = redis::cmd("GET")
+            .arg(redis_url.get_key()?)
+            .query(&mut connection)?;
+        Ok(bytes)
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        let (redis_url, mut connection) = self.connect(url)?;
+        let mut command = redis::cmd("SET");
+        command.arg(redis_url.get_key()?).arg(bytes);
+        if let Some(ttl) = self.get_ttl(&redis_url) {
+            command.arg("PX").arg(ttl.as_millis().max(1) as u64);
+        }
+        command.query::<()>(&mut connection)?;
+        Ok(())
+    }
+    fn update_bytes_at_url(
+        &self,
+        url: &Url,
+        update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let (redis_url, mut connection) = self.connect(url)?;
+        let key = redis_url.get_key()?;
+        for _ in 0..MAX_UPDATE_ATTEMPTS {
+            redis::cmd("WATCH").arg(key).query::<()>(&mut connection)?;
+            let bytes: Option<Vec<u8>> = redis::cmd("GET").arg(key).query(&mut connection)?;
+            let Some(bytes) = bytes else {
+                anyhow::bail!("Record at target location empty!");
+            };
+            let bytes = update(bytes)?;
+            let result: Value = redis::pipe()
+                .atomic()
+                .cmd("SET")
+                .arg(key)
+                .arg(bytes)
+                .arg("KEEPTTL")
+                .query(&mut connection)?;
+            if result != Value::Nil {
+                return Ok(());
+            }
+        }
+        anyhow::bail!("Could not update '{url}' after {MAX_UPDATE_ATTEMPTS} conflicting attempts")
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        let (redis_url, mut connection) = self.connect(url)?;
+        if redis_url.get_key().is_ok() {
+            let exists: bool = redis::cmd("EXISTS")
+                .arg(&redis_url.key)
+                .query(&mut connection)?;
+            if exists {
+                let size: u64 = redis::cmd("STRLEN")
+                    .arg(&redis_url.key)
+                    .query(&mut connection)?;
+                return Ok(UrlMetadata {
+                    exists: true,
+                    size: Some(size),
+                    ..UrlMetadata::default()
+                });
+            }
+        }
+        let prefix = redis_url.container_prefix();
+        let exists =
+            prefix.is_empty() || !scan_keys_with_prefix(&mut connection, &prefix)?.is_empty();
+        Ok(UrlMetadata {
+            exists,
+            is_container: exists,
+            ..UrlMetadata::default()
+        })
+    }
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        let (redis_url, mut connection) = self.connect(url)?;
+        let prefix = redis_url.container_prefix();
+        if prefix.is_empty() {
+            anyhow::bail!("Refusing to delete every key in database {}", redis_url.db);
+        }
+        let keys = scan_keys_with_prefix(&mut connection, &prefix)?;
+        if keys.is_empty() {
+            return Ok(());
+        }
+        if !recursive {
+            anyhow::bail!("Container '{prefix}' is not empty!");
+        }
+        redis::cmd("DEL").arg(keys).query::<()>(&mut connection)?;
+        Ok(())
+    }
+}
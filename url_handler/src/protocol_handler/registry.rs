@@ -1,12 +1,50 @@
-use super::file::FileProtocolHandler;
+use super::archive::ArchiveProtocolHandler;
+use super::consul::{ConsulProtocolHandler, ConsulProtocolHandlerConfig};
+use super::data::DataProtocolHandler;
+use super::env::EnvProtocolHandler;
+use super::exec::{ExecProtocolHandler, ExecProtocolHandlerConfig};
+use super::file::{FileProtocolHandler, FileProtocolHandlerConfig};
+use super::git::{GitProtocolHandler, GitProtocolHandlerConfig};
 use super::http::{HttpProtocolHandler, HttpProtocolHandlerConfig};
+use super::redis::{RedisProtocolHandler, RedisProtocolHandlerConfig};
+use super::mirror::MirrorGroups;
+use super::mount::MountTable;
+use super::overlay::{OverlayProtocolHandler, OverlayProtocolHandlerConfig};
 use super::scp::SCPProtocolHandler;
+use super::sqlite::SqliteProtocolHandler;
+use super::stdio::StdioProtocolHandler;
+use super::history::HistoryConfig;
+use super::trash::TrashConfig;
 use super::KnownProtocolHandler;
+use crate::encryption_handler::{EncryptionHandler, EncryptionHandlerConfig};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ProtocolHandlerConfig {
+    #[serde(default)]
+    file: FileProtocolHandlerConfig,
+    #[serde(default)]
     http: HttpProtocolHandlerConfig,
+    #[serde(default)]
+    exec: ExecProtocolHandlerConfig,
+    #[serde(default)]
+    git: GitProtocolHandlerConfig,
+    #[serde(default)]
+    redis: RedisProtocolHandlerConfig,
+    #[serde(default)]
+    consul: ConsulProtocolHandlerConfig,
+    #[serde(default)]
+    overlay: OverlayProtocolHandlerConfig,
+    #[serde(default)]
+    encryption: EncryptionHandlerConfig,
+    #[serde(default)]
+    trash: TrashConfig,
+    #[serde(default)]
+    history: HistoryConfig,
+    #[serde(default)]
+    mounts: MountTable,
+    #[serde(default)]
+    mirrors: MirrorGroups,
 }
 
 #[derive(Clone, Debug)]
@@ -14,6 +52,33 @@ pub struct ProtocolHandlerRegistry {
     file_handler: KnownProtocolHandler,
     scp_handler: KnownProtocolHandler,
     http_handler: KnownProtocolHandler,
+    data_handler: KnownProtocolHandler,
+    env_handler: KnownProtocolHandler,
+    stdio_handler: KnownProtocolHandler,
+    exec_handler: KnownProtocolHandler,
+    archive_handler: KnownProtocolHandler,
+    git_handler: KnownProtocolHandler,
+    sqlite_handler: KnownProtocolHandler,
+    redis_handler: KnownProtocolHandler,
+    consul_handler: KnownProtocolHandler,
+    overlay_handler: KnownProtocolHandler,
+    encryption_handler: EncryptionHandler,
+    trash_config: TrashConfig,
+    history_config: HistoryConfig,
+    mount_table: MountTable,
+    mirror_groups: MirrorGroups,
+}
+
+// Kept in line with `get_handler_for_protocol`, which also takes `zip+` and `tar+` schemes.
+const REGISTERED_SCHEMES: [&str; 14] = [
+    "file", "scp", "http", "https", "http+unix", "data", "env", "stdio", "exec", "git+file",
+    "sqlite", "redis", "consul", "overlay",
+];
+
+pub(crate) fn is_registered_scheme(scheme: &str) -> bool {
+    REGISTERED_SCHEMES.contains(&scheme)
+        || scheme.starts_with("zip+")
+        || scheme.starts_with("tar+")
 }
 
 impl Default for ProtocolHandlerRegistry {
@@ -26,17 +91,73 @@ impl ProtocolHandlerRegistry {
 
     pub fn new(config: &ProtocolHandlerConfig) -> Self {
         ProtocolHandlerRegistry {
-            file_handler: KnownProtocolHandler::File(FileProtocolHandler::default()),
+            file_handler: KnownProtocolHandler::File(FileProtocolHandler::new(&config.file)),
             scp_handler: KnownProtocolHandler::Scp(SCPProtocolHandler::default()),
             http_handler: KnownProtocolHandler::Http(HttpProtocolHandler::new(&config.http)),
+            data_handler: KnownProtocolHandler::Data(DataProtocolHandler::default()),
+            env_handler: KnownProtocolHandler::Env(EnvProtocolHandler::default()),
+            stdio_handler: KnownProtocolHandler::Stdio(StdioProtocolHandler::default()),
+            exec_handler: KnownProtocolHandler::Exec(ExecProtocolHandler::new(&config.exec)),
+            archive_handler: KnownProtocolHandler::Archive(ArchiveProtocolHandler::new(config)),
+            git_handler: KnownProtocolHandler::Git(GitProtocolHandler::new(
+                &config.git,
+                FileProtocolHandler::new(&config.file),
+            )),
+            sqlite_handler: KnownProtocolHandler::Sqlite(SqliteProtocolHandler::default()),
+            redis_handler: KnownProtocolHandler::Redis(RedisProtocolHandler::new(&config.redis)),
+            consul_handler: KnownProtocolHandler::Consul(ConsulProtocolHandler::new(
+                &config.consul,
+                HttpProtocolHandler::new(&config.http),
+            )),
+            overlay_handler: KnownProtocolHandler::Overlay(OverlayProtocolHandler::new(
+                &config.overlay,
+                config,
+            )),
+            encryption_handler: EncryptionHandler::new(&config.encryption),
+            trash_config: config.trash.clone(),
+            history_config: config.history.clone(),
+            mount_table: config.mounts.clone(),
+            mirror_groups: config.mirrors.clone(),
         }
     }
 
+    pub fn get_encryption_handler(&self) -> &EncryptionHandler {
+        &self.encryption_handler
+    }
+
+    pub fn get_trash_config(&self) -> &TrashConfig {
+        &self.trash_config
+    }
+
+    pub fn get_history_config(&self) -> &HistoryConfig {
+        &self.history_config
+    }
+
+    pub fn get_mount_table(&self) -> &MountTable {
+        &self.mount_table
+    }
+
+    pub fn get_mirror_groups(&self) -> &MirrorGroups {
+        &self.mirror_groups
+    }
+
     pub fn get_handler_for_protocol(&self, protocol: &str) -> Option<&KnownProtocolHandler> {
         let handler = match protocol {
             "file" => &self.file_handler,
             "scp" => &self.scp_handler,
-            "http" | "https" => &self.http_handler,
+            "http" | "https" | "http+unix" => &self.http_handler,
+            "data" => &self.data_handler,
+            "env" => &self.env_handler,
+            "stdio" => &self.stdio_handler,
+            "exec" => &self.exec_handler,
+            "git+file" => &self.git_handler,
+            "sqlite" => &self.sqlite_handler,
+            "redis" => &self.redis_handler,
+            "consul" => &self.consul_handler,
+            "overlay" => &self.overlay_handler,
+            protocol if protocol.starts_with("zip+") || protocol.starts_with("tar+") => {
+                &self.archive_handler
+            }
             _ => return None,
         };
         Some(handler)
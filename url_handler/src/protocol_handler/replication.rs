@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use url::Url;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ReplicationPolicy {
+    #[default]
+    All,
+    // More than half of the targets have to succeed.
+    Quorum,
+    BestEffort,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplicationOutcome {
+    pub url: Url,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplicationReport {
+    pub outcomes: Vec<ReplicationOutcome>,
+}
+
+impl ReplicationReport {
+    pub fn succeeded(&self) -> Vec<&Url> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.error.is_none())
+            .map(|outcome| &outcome.url)
+            .collect()
+    }
+
+    pub fn failed(&self) -> Vec<&ReplicationOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.error.is_some())
+            .collect()
+    }
+
+    pub fn satisfies(&self, policy: &ReplicationPolicy) -> bool {
+        let succeeded = self.succeeded().len();
+        match policy {
+            ReplicationPolicy::All => succeeded == self.outcomes.len(),
+            ReplicationPolicy::Quorum => succeeded * 2 > self.outcomes.len(),
+            ReplicationPolicy::BestEffort => true,
+        }
+    }
+}
+
+// Returned when the policy was not met, targets that succeeded are not rolled back.
+#[derive(Debug)]
+pub struct ReplicationError {
+    policy: ReplicationPolicy,
+    report: ReplicationReport,
+}
+
+impl ReplicationError {
+    pub fn new(policy: ReplicationPolicy, report: ReplicationReport) -> Self {
+        ReplicationError { policy, report }
+    }
+
+    pub fn report(&self) -> &ReplicationReport {
+        &self.report
+    }
+}
+
+impl fmt::Display for ReplicationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "Replication policy {:?} not met, {} of {} targets succeeded",
+            self.policy,
+            self.report.succeeded().len(),
+            self.report.outcomes.len()
+        )?;
+        for outcome in self.report.failed() {
+            let error = outcome.error.as_deref().unwrap_or_default();
+            write!(formatter, "\n  {}: {error}", outcome.url)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ReplicationError {}
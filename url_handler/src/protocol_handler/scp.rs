@@ -2,42 +2,154 @@ use std::collections::HashSet;
 use crate::external_fascade::OpenSSHFascade;
 use anyhow::Result;
 use path_absolutize::*;
-use std::fs::{read_to_string, write};
+use std::fs::{read, write};
 use std::path::PathBuf;
+use std::time::Duration;
 use tempfile::TempDir;
 use url::Url;
 
-use super::ProtocolHandler;
+use super::walk::{build_child_url, WalkEntry, WalkMatcher};
+use super::{ConflictError, ProtocolHandler, UrlMetadata, UrlVersion, WalkOptions};
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(300);
+
+fn is_same_remote(source: &Url, target: &Url) -> bool {
+    source.scheme() == "scp"
+        && target.scheme() == "scp"
+        && source.host_str() == target.host_str()
+        && source.port() == target.port()
+        && source.username() == target.username()
+}
+
+fn is_local_container(url: &Url) -> Result<bool> {
+    let is_container = match url.scheme() {
+        "scp" => OpenSSHFascade::stat_remote_path(url)?.is_container,
+        _ => url.to_file_path().is_ok_and(|path| path.is_dir()),
+    };
+    Ok(is_container)
+}
 
 #[derive(Default, Clone, Debug)]
 pub struct SCPProtocolHandler {}
 
 impl ProtocolHandler for SCPProtocolHandler {
     fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            return Ok(None);
+        };
+        Ok(Some(String::from_utf8(bytes)?))
+    }
+    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+        self.push_bytes_to_url(url, string.as_bytes())
+    }
+    fn delete_string_from_url(&self, url: &Url) -> Result<()> {
+        OpenSSHFascade::delete_remote_file(url)
+    }
+    fn create_empty_string_on_url(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Creating empty strings is not supported by the scp handler!")
+    }
+    fn create_url_container(&self, url: &Url) -> Result<()> {
+        OpenSSHFascade::create_remote_container(url)
+    }
+    fn list_urls_in_url_container(&self, url: &Url) -> Result<HashSet<Url>> {
+        let mut urls: HashSet<Url> = HashSet::default();
+        for (path, _) in OpenSSHFascade::list_remote_path(url, Some(1), false)? {
+            urls.insert(build_child_url(url, &path)?);
+        }
+        Ok(urls)
+    }
+    fn walk_url_container(&self, url: &Url, options: &WalkOptions) -> Result<Vec<Url>> {
+        let matcher = WalkMatcher::new(options)?;
+        let mut entries: Vec<WalkEntry> = Vec::new();
+        for (path, is_container) in
+            OpenSSHFascade::list_remote_path(url, matcher.max_depth(), matcher.follow_symlinks())?
+        {
+            entries.push(WalkEntry {
+                url: build_child_url(url, &path)?,
+                path,
+                is_container,
+            });
+        }
+        Ok(matcher.select(entries))
+    }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
         let tmp_dir: TempDir = TempDir::new()?;
         let target_file = tmp_dir.path().join("string");
         OpenSSHFascade::download_file(url, &target_file)?;
-        let string = read_to_string(target_file)?;
-        Ok(Some(string))
+        let bytes = read(target_file)?;
+        Ok(Some(bytes))
     }
-    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
         let tmp_dir: TempDir = TempDir::new()?;
         let source_file = tmp_dir.path().join("string");
-        write(&source_file, string)?;
+        write(&source_file, bytes)?;
         OpenSSHFascade::upload_file(&source_file, url)?;
         Ok(())
     }
-    fn delete_string_from_url(&self, _: &Url) -> Result<()> {
-        todo!("Delete Operation is not yet implemented for the scp handler!")
+    fn update_bytes_at_url(
+        &self,
+        url: &Url,
+        update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let _lock = OpenSSHFascade::lock_remote_path(url, LOCK_TIMEOUT, LOCK_STALE_AFTER)?;
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            anyhow::bail!("Record at target location empty!");
+        };
+        let bytes = update(bytes)?;
+        self.push_bytes_to_url(url, &bytes)
     }
-    fn create_empty_string_on_url(&self, _: &Url) -> Result<()> {
-        todo!("Create String Operation is not yet implemented for the scp handler!")
+    fn fetch_bytes_with_version(&self, url: &Url) -> Result<(Option<Vec<u8>>, Option<UrlVersion>)> {
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            return Ok((None, None));
+        };
+        let version = UrlVersion::from_content(&bytes);
+        Ok((Some(bytes), Some(version)))
+    }
+    fn push_bytes_to_url_if_version(
+        &self,
+        url: &Url,
+        bytes: &[u8],
+        expected: Option<&UrlVersion>,
+    ) -> Result<()> {
+        let _lock = OpenSSHFascade::lock_remote_path(url, LOCK_TIMEOUT, LOCK_STALE_AFTER)?;
+        let found = OpenSSHFascade::hash_remote_file(url)?.map(UrlVersion::Hash);
+        if found.as_ref() != expected {
+            return Err(ConflictError::new(url, expected, found.as_ref()).into());
+        }
+        self.push_bytes_to_url(url, bytes)
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        OpenSSHFascade::stat_remote_path(url)
+    }
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        OpenSSHFascade::delete_remote_container(url, recursive)
+    }
+    fn ensure_container_can_be_deleted(&self, url: &Url, recursive: bool) -> Result<()> {
+        OpenSSHFascade::ensure_remote_container_can_be_deleted(url, recursive)
     }
-    fn create_url_container(&self, _: &Url) -> Result<()> {
-        todo!("Create Container Container Operation is not yet implemented for the scp handler!")
+    fn try_copy_url(&self, source: &Url, target: &Url) -> Result<bool> {
+        if is_same_remote(source, target) {
+            OpenSSHFascade::copy_on_remote(source, target)?;
+            return Ok(true);
+        }
+        let is_supported = matches!(
+            (source.scheme(), target.scheme()),
+            ("file" | "scp", "scp") | ("scp", "file")
+        );
+        // Plain scp nests directories into existing targets, so containers are streamed instead.
+        if !is_supported || is_local_container(source)? {
+            return Ok(false);
+        }
+        OpenSSHFascade::copy(source, target)?;
+        Ok(true)
     }
-    fn list_urls_in_url_container(&self, _: &Url) -> Result<HashSet<Url>> {
-        todo!("List URL Operation is not yet implemented for the scp handler!")
+    fn try_move_url(&self, source: &Url, target: &Url) -> Result<bool> {
+        if !is_same_remote(source, target) {
+            return Ok(false);
+        }
+        OpenSSHFascade::move_on_remote(source, target)?;
+        Ok(true)
     }
 }
 
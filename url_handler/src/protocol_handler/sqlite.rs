@@ -0,0 +1,326 @@
+use super::walk::build_child_url;
+use super::{ProtocolHandler, UrlMetadata};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use percent_encoding::percent_decode_str;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, TransactionBehavior};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+const DATABASE_EXTENSIONS: [&str; 3] = ["sqlite", "sqlite3", "db"];
+
+#[derive(Clone, Debug, PartialEq)]
+struct SqliteUrl {
+    database: PathBuf,
+    table: Option<String>,
+    key: String,
+}
+
+impl SqliteUrl {
+    // The database is the first existing file, or the first segment with a database extension.
+    fn try_from_url(url: &Url) -> Result<Self> {
+        let Some(segments) = url.path_segments() else {
+            anyhow::bail!("URL '{url}' has no path");
+        };
+        let segments: Vec<String> = segments
+            .map(|segment| Ok(percent_decode_str(segment).decode_utf8()?.to_string()))
+            .collect::<Result<_>>()?;
+
+        let mut database = PathBuf::from("/");
+        let mut database_end = None;
+        for (index, segment) in segments.iter().enumerate() {
+            database.push(segment);
+            let has_extension = segment
+                .rsplit_once('.')
+                .is_some_and(|(_, extension)| DATABASE_EXTENSIONS.contains(&extension));
+            if database.is_file() || has_extension {
+                database_end = Some(index);
+                break;
+            }
+        }
+        let Some(database_end) = database_end else {
+            anyhow::bail!("Could not find a database file in '{url}'");
+        };
+
+        let mut rest = segments[database_end + 1..].iter();
+        let table = match rest.next() {
+            Some(table) if !table.is_empty() => {
+                if !table
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || character == '_')
+                {
+                    anyhow::bail!("Table name '{table}' may only contain [A-Za-z0-9_]");
+                }
+                Some(table.clone())
+            }
+            _ => None,
+        };
+        let key = rest.cloned().collect::<Vec<_>>().join("/");
+        Ok(SqliteUrl {
+            database,
+            table,
+            key,
+        })
+    }
+
+    fn get_table(&self) -> Result<&str> {
+        match &self.table {
+            Some(table) => Ok(table),
+            None => anyhow::bail!("URL does not name a table"),
+        }
+    }
+
+    fn get_key(&self) -> Result<&str> {
+        if self.key.is_empty() || self.key.ends_with('/') {
+            anyhow::bail!("URL does not name a key");
+        }
+        Ok(&self.key)
+    }
+
+    fn container_prefix(&self) -> String {
+        match self.key.trim_end_matches('/') {
+            "" => String::new(),
+            key => format!("{key}/"),
+        }
+    }
+
+    // Reads never create the database or table, they are missing until the first push.
+    fn open_existing(&self) -> Result<Option<Connection>> {
+        if !self.database.is_file() {
+            return Ok(None);
+        }
+        let connection = Connection::open_with_flags(
+            &self.database,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        if let Some(table) = &self.table {
+            let has_table = connection
+                .query_row(
+                    "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                    params![table],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some();
+            if !has_table {
+                return Ok(None);
+            }
+        }
+        Ok(Some(connection))
+    }
+
+    fn open_or_create(&self) -> Result<Connection> {
+        let connection = Connection::open(&self.database)?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        if let Some(table) = &self.table {
+            connection.execute(
+                &format!(
+                    "CREATE TABLE IF NOT EXISTS \"{table}\" \
+                     (key TEXT PRIMARY KEY, value BLOB NOT NULL, modified INTEGER NOT NULL)"
+                ),
+                [],
+            )?;
+        }
+        Ok(connection)
+    }
+}
+
+fn write_value(connection: &Connection, table: &str, key: &str, bytes: &[u8]) -> Result<()> {
+    connection.execute(
+        &format!(
+            "INSERT INTO \"{table}\" (key, value, modified) VALUES (?1, ?2, ?3) \
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, modified = excluded.modified"
+        ),
+        params![key, bytes, Utc::now().timestamp_millis()],
+    )?;
+    Ok(())
+}
+
+fn read_value(connection: &Connection, table: &str, key: &str) -> Result<Option<Vec<u8>>> {
+    let value = connection
+        .query_row(
+            &format!("SELECT value FROM \"{table}\" WHERE key = ?1"),
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(value)
+}
+
+fn list_keys_with_prefix(
+    connection: &Connection,
+    table: &str,
+    prefix: &str,
+) -> Result<Vec<String>> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT key FROM \"{table}\" WHERE substr(key, 1, length(?1)) = ?1 ORDER BY key"
+    ))?;
+    let keys = statement
+        .query_map(params![prefix], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(keys)
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct SqliteProtocolHandler {}
+
+impl ProtocolHandler for SqliteProtocolHandler {
+    fn fetch_string_from_url(&self, url: &Url) -> Result<Option<String>> {
+        let Some(bytes) = self.fetch_bytes_from_url(url)? else {
+            return Ok(None);
+        };
+        Ok(Some(String::from_utf8(bytes)?))
+    }
+    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+        self.push_bytes_to_url(url, string.as_bytes())
+    }
+    fn delete_string_from_url(&self, url: &Url) -> Result<()> {
+        let sqlite_url = SqliteUrl::try_from_url(url)?;
+        let (table, key) = (sqlite_url.get_table()?, sqlite_url.get_key()?);
+        let Some(connection) = sqlite_url.open_existing()? else {
+            anyhow::bail!("Could not find key '{key}' in table '{table}'");
+        };
+        let deleted = connection.execute(
+            &format!("DELETE FROM \"{table}\" WHERE key = ?1"),
+            params![key],
+        )?;
+        if deleted == 0 {
+            anyhow::bail!("Could not find key '{key}' in table '{table}'");
+        }
+        Ok(())
+    }
+    fn create_empty_string_on_url(&self, url: &Url) -> Result<()> {
+        self.push_bytes_to_url(url, &[])
+    }
+    fn create_url_container(&self, url: &Url) -> Result<()> {
+        // Containers are key prefixes, so only the database and table need to exist.
+        SqliteUrl::try_from_url(url)?.open_or_create()?;
+        Ok(())
+    }
+    fn list_urls_in_url_container(&self, url: &Url) -> Result<HashSet<Url>> {
+        let sqlite_url = SqliteUrl::try_from_url(url)?;
+        let Some(connection) = sqlite_url.open_existing()? else {
+            anyhow::bail!("Container '{url}' does not exist");
+        };
+        let mut children: HashSet<String> = HashSet::default();
+        match &sqlite_url.table {
+            None => {
+                let mut statement =
+                    connection.prepare("SELECT name FROM sqlite_master WHERE type = 'table'")?;
+                for table in statement.query_map([], |row| row.get::<_, String>(0))? {
+                    children.insert(format!("{}/", table?));
+                }
+            }
+            Some(table) => {
+                let prefix = sqlite_url.container_prefix();
+                for key in list_keys_with_prefix(&connection, table, &prefix)? {
+                    let rest = &key[prefix.len()..];
+                    match rest.split_once('/') {
+                        Some((container, _)) => children.insert(format!("{container}/")),
+                        None => children.insert(rest.to_string()),
+                    };
+                }
+            }
+        }
+
+        let mut urls: HashSet<Url> = HashSet::default();
+        for child in children {
+            urls.insert(build_child_url(url, &child)?);
+        }
+        Ok(urls)
+    }
+    fn fetch_bytes_from_url(&self, url: &Url) -> Result<Option<Vec<u8>>> {
+        let sqlite_url = SqliteUrl::try_from_url(url)?;
+        let (table, key) = (sqlite_url.get_table()?, sqlite_url.get_key()?);
+        match sqlite_url.open_existing()? {
+            Some(connection) => read_value(&connection, table, key),
+            None => Ok(None),
+        }
+    }
+    fn push_bytes_to_url(&self, url: &Url, bytes: &[u8]) -> Result<()> {
+        let sqlite_url = SqliteUrl::try_from_url(url)?;
+        let (table, key) = (sqlite_url.get_table()?, sqlite_url.get_key()?);
+        write_value(&sqlite_url.open_or_create()?, table, key, bytes)
+    }
+    fn update_bytes_at_url(
+        &self,
+        url: &Url,
+        update: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let sqlite_url = SqliteUrl::try_from_url(url)?;
+        let (table, key) = (sqlite_url.get_table()?, sqlite_url.get_key()?);
+        let Some(mut connection) = sqlite_url.open_existing()? else {
+            anyhow::bail!("Record at target location empty!");
+        };
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let Some(bytes) = read_value(&transaction, table, key)? else {
+            anyhow::bail!("Record at target location empty!");
+        };
+        write_value(&transaction, table, key, &update(bytes)?)?;
+        transaction.commit()?;
+        Ok(())
+    }
+    fn stat(&self, url: &Url) -> Result<UrlMetadata> {
+        let sqlite_url = SqliteUrl::try_from_url(url)?;
+        let Some(connection) = sqlite_url.open_existing()? else {
+            return Ok(UrlMetadata::missing());
+        };
+        let Some(table) = &sqlite_url.table else {
+            return Ok(UrlMetadata {
+                exists: true,
+                is_container: true,
+                ..UrlMetadata::default()
+            });
+        };
+        if !sqlite_url.key.is_empty() && !sqlite_url.key.ends_with('/') {
+            let row: Option<(i64, i64)> = connection
+                .query_row(
+                    &format!("SELECT length(value), modified FROM \"{table}\" WHERE key = ?1"),
+                    params![sqlite_url.key],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            if let Some((size, modified)) = row {
+                return Ok(UrlMetadata {
+                    exists: true,
+                    size: Some(size as u64),
+                    modified: DateTime::from_timestamp_millis(modified),
+                    ..UrlMetadata::default()
+                });
+            }
+        }
+        let prefix = sqlite_url.container_prefix();
+        let exists =
+            prefix.is_empty() || !list_keys_with_prefix(&connection, table, &prefix)?.is_empty();
+        Ok(UrlMetadata {
+            exists,
+            is_container: exists,
+            ..UrlMetadata::default()
+        })
+    }
+    fn delete_url_container(&self, url: &Url, recursive: bool) -> Result<()> {
+        let sqlite_url = SqliteUrl::try_from_url(url)?;
+        let table = sqlite_url.get_table()?;
+        let prefix = sqlite_url.container_prefix();
+        let Some(mut connection) = sqlite_url.open_existing()? else {
+            anyhow::bail!("Container '{url}' does not exist");
+        };
+        let transaction = connection.transaction()?;
+        if !recursive && !list_keys_with_prefix(&transaction, table, &prefix)?.is_empty() {
+            anyhow::bail!("Container '{prefix}' in table '{table}' is not empty!");
+        }
+        match prefix.is_empty() {
+            true => transaction.execute(&format!("DROP TABLE \"{table}\""), [])?,
+            false => transaction.execute(
+                &format!("DELETE FROM \"{table}\" WHERE substr(key, 1, length(?1)) = ?1"),
+                params![prefix],
+            )?,
+        };
+        transaction.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use super::{try_build_url_from_path_buf, ProtocolHandler};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::io::{stdin, stdout, Read, Write};
+use std::path::PathBuf;
+use url::Url;
+
+#[derive(Default, Clone, Debug)]
+pub struct StdioProtocolHandler {}
+
+impl ProtocolHandler for StdioProtocolHandler {
+    fn fetch_string_from_url(&self, _: &Url) -> Result<Option<String>> {
+        let mut string = String::new();
+        stdin().lock().read_to_string(&mut string)?;
+        Ok(Some(string))
+    }
+    fn push_string_to_url(&self, url: &Url, string: &str) -> Result<()> {
+        self.push_bytes_to_url(url, string.as_bytes())
+    }
+    fn delete_string_from_url(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Standard input and output can not be deleted!")
+    }
+    fn create_empty_string_on_url(&self, url: &Url) -> Result<()> {
+        self.push_string_to_url(url, "")
+    }
+    fn create_url_container(&self, _: &Url) -> Result<()> {
+        anyhow::bail!("Standard input and output can not be used as containers!")
+    }
+    fn list_urls_in_url_container(&self, _: &Url) -> Result<HashSet<Url>> {
+        anyhow::bail!("Standard input and output can not be used as containers!")
+    }
+    fn fetch_bytes_from_url(&self, _: &Url) -> Result<Option<Vec<u8>>> {
+        let mut bytes = Vec::new();
+        stdin().lock().read_to_end(&mut bytes)?;
+        Ok(Some(bytes))
+    }
+    fn push_bytes_to_url(&self, _: &Url, bytes: &[u8]) -> Result<()> {
+        let mut handle = stdout().lock();
+        handle.write_all(bytes)?;
+        handle.flush()?;
+        Ok(())
+    }
+    fn is_stored(&self) -> bool {
+        false
+    }
+}
+
+pub fn try_build_url_from_str(string: &str) -> Result<Url> {
+    if string == "-" {
+        return Ok(Url::parse("stdio:")?);
+    }
+    match Url::parse(string) {
+        Ok(url) => Ok(url),
+        Err(_) => try_build_url_from_path_buf(&PathBuf::from(string)),
+    }
+}
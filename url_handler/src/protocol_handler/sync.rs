@@ -0,0 +1,196 @@
+use super::walk::build_child_url;
+use super::{
+    copy_url, create_url_container, delete_string_from_url, delete_url_container,
+    fetch_bytes_from_url, stat_url, walk_url_container, ProtocolHandlerRegistry, UrlMetadata,
+    UrlVersion, WalkOptions,
+};
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use url::Url;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum SyncCompare {
+    #[default]
+    Metadata,
+    Hash,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncOptions {
+    pub delete_extraneous: bool,
+    pub dry_run: bool,
+    pub compare: SyncCompare,
+    pub walk: WalkOptions,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SyncAction {
+    CreateContainer { target: Url },
+    Create { source: Url, target: Url },
+    Update { source: Url, target: Url },
+    Delete { target: Url },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncSummary {
+    pub dry_run: bool,
+    pub actions: Vec<SyncAction>,
+    pub unchanged: Vec<Url>,
+    // Targets that are a container where the source is a record or the other way around.
+    pub conflicts: Vec<Url>,
+}
+
+fn get_relative_path(container: &Url, url: &Url) -> Result<String> {
+    let prefix = format!("{}/", container.as_str().trim_end_matches('/'));
+    let Some(path) = url.as_str().strip_prefix(&prefix) else {
+        anyhow::bail!("Url '{url}' is not inside of '{container}'");
+    };
+    let path = percent_decode_str(path.trim_end_matches('/')).decode_utf8()?;
+    Ok(path.to_string())
+}
+
+fn walk_relative(
+    container: &Url,
+    options: &WalkOptions,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<BTreeMap<String, Url>> {
+    let mut entries = BTreeMap::new();
+    for url in walk_url_container(container, options, registry)? {
+        entries.insert(get_relative_path(container, &url)?, url);
+    }
+    Ok(entries)
+}
+
+fn is_below_any(path: &str, containers: &[&str]) -> bool {
+    containers.iter().any(|container| {
+        path.strip_prefix(container)
+            .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+fn is_unchanged_by_metadata(source: &UrlMetadata, target: &UrlMetadata) -> bool {
+    if source.size.is_none() || source.size != target.size {
+        return false;
+    }
+    match (source.modified, target.modified) {
+        // Some protocols only report whole seconds.
+        (Some(source), Some(target)) => target.timestamp() >= source.timestamp(),
+        _ => source.version.is_some() && source.version == target.version,
+    }
+}
+
+fn is_unchanged_by_hash(
+    source: &Url,
+    target: &Url,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<bool> {
+    let hash = |url: &Url| -> Result<Option<UrlVersion>> {
+        let bytes = fetch_bytes_from_url(url, registry)?;
+        Ok(bytes.map(|bytes| UrlVersion::from_content(&bytes)))
+    };
+    Ok(hash(source)? == hash(target)?)
+}
+
+pub fn sync_url_containers(
+    source: &Url,
+    target: &Url,
+    options: &SyncOptions,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<SyncSummary> {
+    let mut summary = SyncSummary {
+        dry_run: options.dry_run,
+        ..SyncSummary::default()
+    };
+    let target_exists = stat_url(target, registry)?.exists;
+    if !target_exists {
+        summary.actions.push(SyncAction::CreateContainer {
+            target: target.clone(),
+        });
+        if !options.dry_run {
+            create_url_container(target, registry)?;
+        }
+    }
+
+    let source_entries = walk_relative(source, &options.walk, registry)?;
+    let mut conflicting_paths: Vec<&str> = Vec::new();
+    for (path, source_url) in &source_entries {
+        if is_below_any(path, &conflicting_paths) {
+            continue;
+        }
+        let target_url = build_child_url(target, path)?;
+        let source_metadata = stat_url(source_url, registry)?;
+        let target_metadata = match target_exists {
+            true => stat_url(&target_url, registry)?,
+            false => UrlMetadata::missing(),
+        };
+        if target_metadata.exists && source_metadata.is_container != target_metadata.is_container {
+            log::warn!("Not syncing '{source_url}' as '{target_url}' has a different type");
+            conflicting_paths.push(path);
+            summary.conflicts.push(target_url);
+            continue;
+        }
+
+        let action = match (source_metadata.is_container, target_metadata.exists) {
+            (true, true) => None,
+            (true, false) => Some(SyncAction::CreateContainer {
+                target: target_url.clone(),
+            }),
+            (false, false) => Some(SyncAction::Create {
+                source: source_url.clone(),
+                target: target_url.clone(),
+            }),
+            (false, true) => {
+                let is_unchanged = match options.compare {
+                    SyncCompare::Metadata => {
+                        is_unchanged_by_metadata(&source_metadata, &target_metadata)
+                    }
+                    SyncCompare::Hash => is_unchanged_by_hash(source_url, &target_url, registry)?,
+                };
+                match is_unchanged {
+                    true => None,
+                    false => Some(SyncAction::Update {
+                        source: source_url.clone(),
+                        target: target_url.clone(),
+                    }),
+                }
+            }
+        };
+
+        let Some(action) = action else {
+            summary.unchanged.push(target_url);
+            continue;
+        };
+        if !options.dry_run {
+            match &action {
+                SyncAction::CreateContainer { target } => create_url_container(target, registry)?,
+                SyncAction::Create { source, target } | SyncAction::Update { source, target } => {
+                    copy_url(source, target, registry)?
+                }
+                SyncAction::Delete { .. } => {}
+            }
+        }
+        summary.actions.push(action);
+    }
+
+    if !options.delete_extraneous || !target_exists {
+        return Ok(summary);
+    }
+    let target_entries = walk_relative(target, &options.walk, registry)?;
+    for (path, target_url) in target_entries.iter().rev() {
+        if source_entries.contains_key(path) || is_below_any(path, &conflicting_paths) {
+            continue;
+        }
+        if !options.dry_run {
+            match stat_url(target_url, registry)?.is_container {
+                true => delete_url_container(target_url, true, registry)?,
+                false => delete_string_from_url(target_url, registry)?,
+            }
+        }
+        summary.actions.push(SyncAction::Delete {
+            target: target_url.clone(),
+        });
+    }
+    Ok(summary)
+}
@@ -0,0 +1,91 @@
+use super::walk::{build_child_url, get_entry_name, is_url_container};
+use super::{KnownProtocolHandler, ProtocolHandler, ProtocolHandlerRegistry};
+use anyhow::Result;
+use std::mem::discriminant;
+use url::Url;
+
+fn get_handler<'a>(
+    url: &Url,
+    registry: &'a ProtocolHandlerRegistry,
+) -> Result<&'a KnownProtocolHandler> {
+    let protocol = url.scheme();
+    let Some(handler) = registry.get_handler_for_protocol(protocol) else {
+        anyhow::bail!("Could not find handler for protocol '{protocol}'");
+    };
+    Ok(handler)
+}
+
+fn copy_url_by_streaming(
+    source: &Url,
+    target: &Url,
+    source_handler: &KnownProtocolHandler,
+    target_handler: &KnownProtocolHandler,
+) -> Result<()> {
+    if !is_url_container(source_handler, source) {
+        let Some(bytes) = source_handler.fetch_bytes_from_url(source)? else {
+            anyhow::bail!("Could not copy '{source}' because it is empty!");
+        };
+        return target_handler.push_bytes_to_url(target, &bytes);
+    }
+
+    target_handler.create_url_container(target)?;
+    let mut children: Vec<Url> = source_handler
+        .list_urls_in_url_container(source)?
+        .into_iter()
+        .collect();
+    children.sort();
+    for child in children {
+        let child_target = build_child_url(target, &get_entry_name(&child)?)?;
+        copy_url_by_streaming(&child, &child_target, source_handler, target_handler)?;
+    }
+    Ok(())
+}
+
+fn ensure_target_outside_source(source: &Url, target: &Url) -> Result<()> {
+    let container = format!("{}/", source.as_str().trim_end_matches('/'));
+    if target.as_str().starts_with(&container) {
+        anyhow::bail!("Can not transfer '{source}' into itself at '{target}'");
+    }
+    Ok(())
+}
+
+pub fn copy_url(source: &Url, target: &Url, registry: &ProtocolHandlerRegistry) -> Result<()> {
+    let source = &registry.get_mount_table().resolve(source)?;
+    let target = &registry.get_mount_table().resolve(target)?;
+    ensure_target_outside_source(source, target)?;
+    let source_handler = get_handler(source, registry)?;
+    let target_handler = get_handler(target, registry)?;
+
+    if source_handler.try_copy_url(source, target)? {
+        return Ok(());
+    }
+    if discriminant(source_handler) != discriminant(target_handler)
+        && target_handler.try_copy_url(source, target)?
+    {
+        return Ok(());
+    }
+    copy_url_by_streaming(source, target, source_handler, target_handler)
+}
+
+pub fn move_url(source: &Url, target: &Url, registry: &ProtocolHandlerRegistry) -> Result<()> {
+    let source = &registry.get_mount_table().resolve(source)?;
+    let target = &registry.get_mount_table().resolve(target)?;
+    ensure_target_outside_source(source, target)?;
+    let source_handler = get_handler(source, registry)?;
+    let target_handler = get_handler(target, registry)?;
+
+    if source_handler.try_move_url(source, target)? {
+        return Ok(());
+    }
+    if discriminant(source_handler) != discriminant(target_handler)
+        && target_handler.try_move_url(source, target)?
+    {
+        return Ok(());
+    }
+    let is_container = is_url_container(source_handler, source);
+    copy_url(source, target, registry)?;
+    match is_container {
+        true => source_handler.delete_url_container(source, true),
+        false => source_handler.delete_string_from_url(source),
+    }
+}
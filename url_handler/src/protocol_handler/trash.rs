@@ -0,0 +1,173 @@
+use super::walk::{build_child_url, get_entry_name, get_parent_url};
+use super::{
+    create_url_container, delete_string_from_url, delete_url_container, fetch_string_from_url,
+    list_urls_in_url_container, move_url, push_string_to_url, stat_url, url_exists,
+    ProtocolHandlerRegistry,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+
+const DEFAULT_TRASH_NAME: &str = ".trash";
+const TRASH_INFO_EXTENSION: &str = "trashinfo";
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TrashConfig {
+    #[serde(default)]
+    enabled: bool,
+    name: Option<String>,
+    roots: Option<Vec<String>>,
+    #[serde(default, with = "humantime_serde")]
+    retention: Option<Duration>,
+}
+
+impl TrashConfig {
+    fn get_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(DEFAULT_TRASH_NAME)
+    }
+
+    fn is_in_trash(&self, url: &Url) -> bool {
+        url.path_segments()
+            .is_some_and(|mut segments| segments.any(|segment| segment == self.get_name()))
+    }
+
+    pub fn should_trash(&self, url: &Url) -> bool {
+        self.enabled && !self.is_in_trash(url)
+    }
+
+    // Records go to the trash of the closest configured root, or next to them otherwise.
+    pub fn get_trash_for_url(&self, url: &Url) -> Result<Url> {
+        let root = self
+            .roots
+            .iter()
+            .flatten()
+            .filter(|root| is_inside_of(url, root))
+            .max_by_key(|root| root.len());
+        let root = match root {
+            Some(root) => Url::parse(root)?,
+            None => get_parent_url(url)?,
+        };
+        build_child_url(&root, self.get_name())
+    }
+}
+
+fn is_inside_of(url: &Url, root: &str) -> bool {
+    let root = root.trim_end_matches('/');
+    match url.as_str().strip_prefix(root) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TrashInfo {
+    original: Url,
+    deleted: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub url: Url,
+    pub original: Url,
+    pub deleted: DateTime<Utc>,
+}
+
+fn build_info_url(item: &Url) -> Result<Url> {
+    Ok(Url::parse(&format!("{item}.{TRASH_INFO_EXTENSION}"))?)
+}
+
+pub(crate) fn move_to_trash(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<Url> {
+    if !url_exists(url, registry)? {
+        anyhow::bail!("Could not move '{url}' to the trash because it does not exist!");
+    }
+    let trash = registry.get_trash_config().get_trash_for_url(url)?;
+    if !url_exists(&trash, registry)? {
+        create_url_container(&trash, registry)?;
+    }
+
+    let deleted = Utc::now();
+    let name = format!(
+        "{}-{}",
+        deleted.format("%Y%m%dT%H%M%S%.6fZ"),
+        get_entry_name(url)?
+    );
+    let item = build_child_url(&trash, &name)?;
+    let info = TrashInfo {
+        original: url.clone(),
+        deleted,
+    };
+    move_url(url, &item, registry)?;
+    push_string_to_url(&build_info_url(&item)?, &toml::to_string(&info)?, registry)?;
+    Ok(item)
+}
+
+pub fn get_trash_container(url: &Url, registry: &ProtocolHandlerRegistry) -> Result<Url> {
+    registry.get_trash_config().get_trash_for_url(url)
+}
+
+pub fn list_trash(trash: &Url, registry: &ProtocolHandlerRegistry) -> Result<Vec<TrashEntry>> {
+    let mut entries = Vec::new();
+    for info_url in list_urls_in_url_container(trash, registry)? {
+        let Some(item) = info_url
+            .as_str()
+            .strip_suffix(&format!(".{TRASH_INFO_EXTENSION}"))
+        else {
+            continue;
+        };
+        let Some(info) = fetch_string_from_url(&info_url, registry)? else {
+            continue;
+        };
+        let info: TrashInfo = toml::from_str(&info)?;
+        entries.push(TrashEntry {
+            url: Url::parse(item)?,
+            original: info.original,
+            deleted: info.deleted,
+        });
+    }
+    entries.sort_by_key(|entry| entry.deleted);
+    Ok(entries)
+}
+
+pub fn restore_from_trash(item: &Url, registry: &ProtocolHandlerRegistry) -> Result<Url> {
+    let info_url = build_info_url(item)?;
+    let Some(info) = fetch_string_from_url(&info_url, registry)? else {
+        anyhow::bail!("Could not find trash info for '{item}'");
+    };
+    let info: TrashInfo = toml::from_str(&info)?;
+    if url_exists(&info.original, registry)? {
+        anyhow::bail!(
+            "Can not restore '{item}' because '{}' exists",
+            info.original
+        );
+    }
+    move_url(item, &info.original, registry)?;
+    delete_string_from_url(&info_url, registry)?;
+    Ok(info.original)
+}
+
+pub fn purge_trash(
+    trash: &Url,
+    older_than: Option<Duration>,
+    registry: &ProtocolHandlerRegistry,
+) -> Result<Vec<TrashEntry>> {
+    let Some(older_than) = older_than.or(registry.get_trash_config().retention) else {
+        anyhow::bail!("Can not purge trash without a retention duration!");
+    };
+    let cutoff = Utc::now() - chrono::Duration::from_std(older_than)?;
+
+    let mut purged = Vec::new();
+    for entry in list_trash(trash, registry)? {
+        if entry.deleted > cutoff {
+            continue;
+        }
+        match stat_url(&entry.url, registry)?.is_container {
+            true => delete_url_container(&entry.url, true, registry)?,
+            false => delete_string_from_url(&entry.url, registry)?,
+        }
+        delete_string_from_url(&build_info_url(&entry.url)?, registry)?;
+        purged.push(entry);
+    }
+    Ok(purged)
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use url::Url;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UrlVersion {
+    ETag(String),
+    Modified { modified: DateTime<Utc>, size: u64 },
+    Hash(String),
+    Index(u64),
+}
+
+impl UrlVersion {
+    pub fn from_content(bytes: &[u8]) -> Self {
+        let digest = Sha256::digest(bytes);
+        let hash = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+        UrlVersion::Hash(hash)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConflictError {
+    url: Url,
+    expected: Option<UrlVersion>,
+    found: Option<UrlVersion>,
+}
+
+impl ConflictError {
+    pub fn new(url: &Url, expected: Option<&UrlVersion>, found: Option<&UrlVersion>) -> Self {
+        ConflictError {
+            url: url.clone(),
+            expected: expected.cloned(),
+            found: found.cloned(),
+        }
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn expected(&self) -> Option<&UrlVersion> {
+        self.expected.as_ref()
+    }
+
+    pub fn found(&self) -> Option<&UrlVersion> {
+        self.found.as_ref()
+    }
+}
+
+impl fmt::Display for ConflictError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "Conflict at '{}': expected version {:?} but found {:?}",
+            self.url, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ConflictError {}
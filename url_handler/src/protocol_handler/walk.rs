@@ -0,0 +1,187 @@
+use super::ProtocolHandler;
+use anyhow::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use url::Url;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum WalkFilter {
+    #[default]
+    All,
+    FilesOnly,
+    ContainersOnly,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WalkOptions {
+    pub max_depth: Option<usize>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub filter: WalkFilter,
+    pub include_hidden: bool,
+    pub follow_symlinks: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct WalkEntry {
+    pub url: Url,
+    pub path: String,
+    pub is_container: bool,
+}
+
+pub(crate) struct WalkMatcher {
+    options: WalkOptions,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(builder.build()?)
+}
+
+impl WalkMatcher {
+    pub fn new(options: &WalkOptions) -> Result<Self> {
+        let include = match options.include.is_empty() {
+            true => None,
+            false => Some(build_glob_set(&options.include)?),
+        };
+        Ok(WalkMatcher {
+            options: options.clone(),
+            include,
+            exclude: build_glob_set(&options.exclude)?,
+        })
+    }
+
+    pub fn follow_symlinks(&self) -> bool {
+        self.options.follow_symlinks
+    }
+
+    pub fn max_depth(&self) -> Option<usize> {
+        self.options.max_depth
+    }
+
+    pub fn should_descend(&self, depth: usize) -> bool {
+        self.options
+            .max_depth
+            .is_none_or(|max_depth| depth < max_depth)
+    }
+
+    // Hidden or excluded paths are pruned together with everything below them.
+    pub fn accepts_path(&self, path: &str) -> bool {
+        let mut prefix = String::new();
+        for component in path.split('/').filter(|component| !component.is_empty()) {
+            if !self.options.include_hidden && component.starts_with('.') {
+                return false;
+            }
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(component);
+            if self.exclude.is_match(&prefix) {
+                return false;
+            }
+        }
+        true
+    }
+
+    // Direct children are at depth 1, so a `max_depth` of 0 selects nothing on every protocol.
+    pub fn select(&self, entries: Vec<WalkEntry>) -> Vec<Url> {
+        let mut entries: Vec<WalkEntry> = entries
+            .into_iter()
+            .filter(|entry| {
+                self.options
+                    .max_depth
+                    .is_none_or(|max_depth| entry.path.split('/').count() <= max_depth)
+            })
+            .filter(|entry| self.accepts_path(&entry.path))
+            .filter(|entry| match self.options.filter {
+                WalkFilter::All => true,
+                WalkFilter::FilesOnly => !entry.is_container,
+                WalkFilter::ContainersOnly => entry.is_container,
+            })
+            .filter(|entry| match &self.include {
+                None => true,
+                Some(include) => include.is_match(&entry.path),
+            })
+            .collect();
+        entries.sort_by(|left, right| left.path.cmp(&right.path));
+        entries.into_iter().map(|entry| entry.url).collect()
+    }
+}
+
+pub(crate) fn get_entry_name(url: &Url) -> Result<String> {
+    let name = url
+        .path()
+        .trim_end_matches('/')
+        .rsplit(['/', '!'])
+        .next()
+        .unwrap_or_default();
+    Ok(percent_decode_str(name).decode_utf8()?.to_string())
+}
+
+pub(crate) fn build_child_url(url: &Url, path: &str) -> Result<Url> {
+    let mut child = url.clone();
+    let Ok(mut segments) = child.path_segments_mut() else {
+        anyhow::bail!("Could not build child url for '{url}'");
+    };
+    segments.pop_if_empty().extend(path.split('/'));
+    drop(segments);
+    Ok(child)
+}
+
+pub(crate) fn get_parent_url(url: &Url) -> Result<Url> {
+    let mut parent = url.clone();
+    let Ok(mut segments) = parent.path_segments_mut() else {
+        anyhow::bail!("Could not find parent container of '{url}'");
+    };
+    segments.pop_if_empty().pop();
+    drop(segments);
+    Ok(parent)
+}
+
+pub(crate) fn is_url_container<H: ProtocolHandler + ?Sized>(handler: &H, url: &Url) -> bool {
+    match handler.stat(url) {
+        Ok(metadata) => metadata.is_container,
+        Err(_) => url.path().ends_with('/'),
+    }
+}
+
+pub(crate) fn walk_url_container_by_listing<H: ProtocolHandler + ?Sized>(
+    handler: &H,
+    url: &Url,
+    options: &WalkOptions,
+) -> Result<Vec<Url>> {
+    let matcher = WalkMatcher::new(options)?;
+    let mut entries: Vec<WalkEntry> = Vec::new();
+    let mut queue: VecDeque<(Url, String, usize)> =
+        VecDeque::from([(url.clone(), String::new(), 0)]);
+
+    while let Some((container, container_path, depth)) = queue.pop_front() {
+        for child in handler.list_urls_in_url_container(&container)? {
+            let name = get_entry_name(&child)?;
+            let path = match container_path.is_empty() {
+                true => name,
+                false => format!("{container_path}/{name}"),
+            };
+            if !matcher.accepts_path(&path) {
+                continue;
+            }
+            let is_container = is_url_container(handler, &child);
+            if is_container && matcher.should_descend(depth + 1) {
+                queue.push_back((child.clone(), path.clone(), depth + 1));
+            }
+            entries.push(WalkEntry {
+                url: child,
+                path,
+                is_container,
+            });
+        }
+    }
+    Ok(matcher.select(entries))
+}
@@ -2,8 +2,36 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
+mod archive;
+mod compression;
+mod consul;
+mod data;
+mod delete;
+mod encryption;
+mod env;
+mod exec;
 mod file;
 mod formats;
+mod git;
+mod history;
+mod http;
+mod http_method;
+mod metadata;
+mod mirror;
+mod mount;
+mod overlay;
+mod redis;
+mod replication;
+mod scp;
+mod sqlite;
+mod stdio;
+mod sync;
+mod transfer;
+mod trash;
+mod unix_socket;
+mod update;
+mod version;
+mod walk;
 mod url_handler;
 
 use super::format_handler::*;
@@ -0,0 +1,259 @@
+use super::*;
+use flate2::{write::GzEncoder, Compression};
+use std::io::Write;
+use tempfile::TempDir;
+use url::Url;
+
+fn build_archive_url(kind: &str, archive: &Url, member: &str) -> Url {
+    Url::parse(&format!("{kind}+{archive}!/{member}")).expect("Could not build url")
+}
+
+#[test]
+fn string_can_be_fetched_from_zip_after_pushing() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.zip"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    let payload: String = "Foobar".to_string();
+
+    create_url_container(&build_archive_url("zip", &archive, ""), &registry)
+        .expect("Could not create archive");
+    let url = build_archive_url("zip", &archive, "conf/app.toml");
+    push_string_to_url(&url, &payload, &registry).expect("Could not push record");
+
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some(payload), record);
+}
+
+#[test]
+fn zip_member_can_be_overwritten_and_deleted() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.zip"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    create_url_container(&build_archive_url("zip", &archive, ""), &registry)
+        .expect("Could not create archive");
+
+    let url = build_archive_url("zip", &archive, "app.toml");
+    let other_url = build_archive_url("zip", &archive, "other.toml");
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push record");
+    push_string_to_url(&other_url, "Bar", &registry).expect("Could not push record");
+    push_string_to_url(&url, "Foobar", &registry).expect("Could not push record");
+
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foobar".to_string()), record);
+
+    delete_string_from_url(&url, &registry).expect("Could not delete record");
+    assert!(fetch_string_from_url(&url, &registry).is_err());
+    let record = fetch_string_from_url(&other_url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Bar".to_string()), record);
+}
+
+#[test]
+fn urls_can_be_discovered_from_archive_directories() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.zip"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    create_url_container(&build_archive_url("zip", &archive, ""), &registry)
+        .expect("Could not create archive");
+
+    let good_url = build_archive_url("zip", &archive, "conf/good_record");
+    let nested_url = build_archive_url("zip", &archive, "conf/nested/record");
+    let empty_container_url = build_archive_url("zip", &archive, "conf/empty/");
+    create_empty_string_on_url(&good_url, &registry).expect("Could not push record");
+    create_empty_string_on_url(&nested_url, &registry).expect("Could not push record");
+    create_url_container(&empty_container_url, &registry).expect("Could not create container");
+
+    let candidate = list_urls_in_url_container(&build_archive_url("zip", &archive, ""), &registry)
+        .expect("Could not list urls");
+    assert_eq!(
+        HashSet::from([build_archive_url("zip", &archive, "conf/")]),
+        candidate
+    );
+
+    let candidate =
+        list_urls_in_url_container(&build_archive_url("zip", &archive, "conf"), &registry)
+            .expect("Could not list urls");
+    assert_eq!(
+        HashSet::from([
+            good_url,
+            build_archive_url("zip", &archive, "conf/nested/"),
+            empty_container_url,
+        ]),
+        candidate
+    );
+}
+
+#[test]
+fn string_can_be_fetched_from_existing_tar_gz() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive_file = tmp_dir.path().join("bundle.tar.gz");
+    let payload: String = "Foobar".to_string();
+
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(payload.len() as u64);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, "./conf/app.toml", payload.as_bytes())
+        .expect("Could not append member");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&builder.into_inner().expect("Could not build tar"))
+        .expect("Could not compress tar");
+    std::fs::write(
+        &archive_file,
+        encoder.finish().expect("Could not compress tar"),
+    )
+    .expect("Could not write archive");
+
+    let archive = try_build_url_from_path_buf(&archive_file).expect("Could not build url");
+    let url = build_archive_url("tar", &archive, "conf/app.toml");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some(payload), record);
+
+    push_string_to_url(&url, "Barfoo", &registry).expect("Could not push record");
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Barfoo".to_string()), record);
+}
+
+#[test]
+fn struct_can_be_built_from_tar_member() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.tar"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    create_url_container(&build_archive_url("tar", &archive, ""), &registry)
+        .expect("Could not create archive");
+
+    let good_record = TestStruct::build_foo();
+    let url = build_archive_url("tar", &archive, "app.toml");
+    let string = toml::to_string(&good_record).expect("Could not serialize record");
+    push_string_to_url(&url, &string, &registry).expect("Could not push record");
+
+    let candidate: TestStruct =
+        build_record_from_url(&url, &registry, &FormatHandlerRegistry::default())
+            .expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+}
+
+#[test]
+fn archive_url_without_member_separator_is_rejected() {
+    let url = Url::parse("zip+file:///tmp/bundle.zip").expect("Could not build url");
+    assert!(fetch_string_from_url(&url, &ProtocolHandlerRegistry::default()).is_err());
+}
+
+#[test]
+fn pushing_to_missing_archive_creates_it() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let registry = ProtocolHandlerRegistry::default();
+    for (kind, file_name) in [("zip", "bundle.zip"), ("tar", "bundle.tar.gz")] {
+        let archive = try_build_url_from_path_buf(&tmp_dir.path().join(file_name))
+            .expect("Could not build url");
+        let url = build_archive_url(kind, &archive, "conf/app.toml");
+
+        push_string_to_url(&url, "Foobar", &registry).expect("Could not push record");
+
+        let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+        assert_eq!(Some("Foobar".to_string()), record);
+    }
+}
+
+#[test]
+fn tar_headers_and_links_survive_pushing_other_members() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive_file = tmp_dir.path().join("bundle.tar");
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(3);
+    header.set_mode(0o600);
+    header.set_mtime(1_000_000);
+    builder
+        .append_data(&mut header, "secret.toml", "Foo".as_bytes())
+        .expect("Could not append member");
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_size(0);
+    header.set_mtime(1_000_000);
+    builder
+        .append_link(&mut header, "current.toml", "secret.toml")
+        .expect("Could not append link");
+    std::fs::write(
+        &archive_file,
+        builder.into_inner().expect("Could not build tar"),
+    )
+    .expect("Could not write archive");
+
+    let archive = try_build_url_from_path_buf(&archive_file).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    push_string_to_url(
+        &build_archive_url("tar", &archive, "app.toml"),
+        "Bar",
+        &registry,
+    )
+    .expect("Could not push record");
+    let link = build_archive_url("tar", &archive, "current.toml");
+    assert!(fetch_string_from_url(&link, &registry).is_err());
+    assert!(push_string_to_url(&link, "Baz", &registry).is_err());
+
+    let bytes = std::fs::read(&archive_file).expect("Could not read archive");
+    let mut tar_archive = tar::Archive::new(bytes.as_slice());
+    let mut names = Vec::new();
+    for entry in tar_archive.entries().expect("Could not read entries") {
+        let entry = entry.expect("Could not read entry");
+        let name = entry
+            .path()
+            .expect("Bad path")
+            .to_string_lossy()
+            .to_string();
+        let header = entry.header();
+        match name.as_str() {
+            "secret.toml" => {
+                assert_eq!(0o600, header.mode().expect("No mode"));
+                assert_eq!(1_000_000, header.mtime().expect("No mtime"));
+            }
+            "current.toml" => {
+                assert_eq!(tar::EntryType::Symlink, header.entry_type());
+                let link_name = entry.link_name().expect("Bad link").expect("No link");
+                assert_eq!(std::path::Path::new("secret.toml"), link_name.as_ref());
+            }
+            _ => {}
+        }
+        names.push(name);
+    }
+    assert_eq!(vec!["secret.toml", "current.toml", "app.toml"], names);
+}
+
+#[test]
+fn zip_members_are_copied_unchanged_when_pushing_other_members() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive_file = tmp_dir.path().join("bundle.zip");
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default().unix_permissions(0o600);
+    writer
+        .start_file("secret.toml", options)
+        .expect("Could not start member");
+    writer.write_all(b"Foo").expect("Could not write member");
+    let bytes = writer.finish().expect("Could not build zip").into_inner();
+    std::fs::write(&archive_file, bytes).expect("Could not write archive");
+
+    let archive = try_build_url_from_path_buf(&archive_file).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    push_string_to_url(
+        &build_archive_url("zip", &archive, "app.toml"),
+        "Bar",
+        &registry,
+    )
+    .expect("Could not push record");
+
+    let bytes = std::fs::read(&archive_file).expect("Could not read archive");
+    let mut zip_archive =
+        zip::ZipArchive::new(std::io::Cursor::new(bytes)).expect("Could not read zip");
+    let secret = zip_archive
+        .by_name("secret.toml")
+        .expect("Member is missing");
+    assert_eq!(Some(0o100600), secret.unix_mode());
+}
@@ -0,0 +1,132 @@
+use super::*;
+use crate::compression_handler::*;
+use std::fs::{read, write};
+use tempfile::TempDir;
+use url::Url;
+
+fn assert_record_round_trip(file_name: &str) {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join(file_name);
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let good_record = NestedStruct::build_struct_with_items();
+
+    push_record_to_url(&url, &good_record, &protocol_handlers, &format_handlers)
+        .expect("Could not push record");
+    let candidate: NestedStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+
+    assert_eq!(good_record, candidate);
+}
+
+#[test]
+fn struct_can_be_stored_in_gzip_compressed_json() {
+    assert_record_round_trip("test.json.gz");
+}
+
+#[test]
+fn struct_can_be_stored_in_zstd_compressed_toml() {
+    assert_record_round_trip("test.toml.zst");
+}
+
+#[test]
+fn struct_can_be_stored_in_bzip2_compressed_toml() {
+    assert_record_round_trip("test.toml.bz2");
+}
+
+#[test]
+fn struct_can_be_stored_in_xz_compressed_json() {
+    assert_record_round_trip("test.json.xz");
+}
+
+#[test]
+fn compressed_record_is_written_compressed() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test.toml.gz");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let good_record = TestStruct::build_foo();
+
+    push_record_to_url(
+        &url,
+        &good_record,
+        &ProtocolHandlerRegistry::default(),
+        &FormatHandlerRegistry::default(),
+    )
+    .expect("Could not push record");
+
+    let bytes = read(&target_file).expect("Could not read file");
+    let string = String::from_utf8(
+        GzipHandler::default()
+            .decompress(&bytes)
+            .expect("Could not decompress"),
+    )
+    .expect("Could not decode string");
+    let candidate: TestStruct = toml::from_str(&string).expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+}
+
+#[test]
+fn externally_compressed_record_can_be_built() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test.json.zst");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let good_record = TestStruct::build_foo();
+    let string = serde_json::to_string(&good_record).expect("Could not serialize record");
+    write(
+        &target_file,
+        ZstdHandler::default()
+            .compress(string.as_bytes())
+            .expect("Could not compress"),
+    )
+    .expect("Could not write file");
+
+    let candidate: TestStruct = build_record_from_url(
+        &url,
+        &ProtocolHandlerRegistry::default(),
+        &FormatHandlerRegistry::default(),
+    )
+    .expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+}
+
+#[test]
+fn compressed_record_can_be_stored_in_compressed_tar() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.tar.xz"))
+        .expect("Could not build url");
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let format_handlers = FormatHandlerRegistry::default();
+    create_url_container(
+        &Url::parse(&format!("tar+{archive}!/")).expect("Could not build url"),
+        &protocol_handlers,
+    )
+    .expect("Could not create archive");
+
+    let url = Url::parse(&format!("tar+{archive}!/conf/app.json.gz")).expect("Could not build url");
+    let good_record = TestStruct::build_foo();
+    push_record_to_url(&url, &good_record, &protocol_handlers, &format_handlers)
+        .expect("Could not push record");
+
+    let candidate: TestStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+
+    let bytes = read(tmp_dir.path().join("bundle.tar.xz")).expect("Could not read archive");
+    assert!(XzHandler::default().decompress(&bytes).is_ok());
+}
+
+#[test]
+fn compression_without_format_can_not_be_pushed() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url =
+        try_build_url_from_path_buf(&tmp_dir.path().join("test.gz")).expect("Could not build url");
+
+    let result = push_record_to_url(
+        &url,
+        &TestStruct::build_foo(),
+        &ProtocolHandlerRegistry::default(),
+        &FormatHandlerRegistry::default(),
+    );
+    assert!(result.is_err());
+}
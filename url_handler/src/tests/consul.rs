@@ -0,0 +1,266 @@
+use super::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use percent_encoding::percent_decode_str;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tiny_http::{Method, Request, Response, Server};
+
+const TOKEN: &str = "secret";
+
+type Entries = Arc<Mutex<(BTreeMap<String, (Vec<u8>, u64)>, u64)>>;
+
+// Implements the subset of the Consul KV API used by the consul handler.
+struct ConsulTestServer {
+    server: Arc<Server>,
+    entries: Entries,
+}
+
+impl ConsulTestServer {
+    fn start() -> ConsulTestServer {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("Could not start server"));
+        let entries: Entries = Arc::new(Mutex::new((BTreeMap::new(), 1)));
+
+        let thread_server = server.clone();
+        let thread_entries = entries.clone();
+        thread::spawn(move || {
+            for request in thread_server.incoming_requests() {
+                handle_request(request, &thread_entries);
+            }
+        });
+        ConsulTestServer { server, entries }
+    }
+
+    fn url(&self, path: &str) -> Url {
+        let address = self
+            .server
+            .server_addr()
+            .to_ip()
+            .expect("Server is not on ip");
+        Url::parse(&format!("consul://{address}{path}")).expect("Could not build url")
+    }
+
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let entries = self.entries.lock().expect("Could not lock entries");
+        entries.0.get(key).map(|(value, _)| value.clone())
+    }
+
+    fn build_registry(&self, token: Option<&str>) -> ProtocolHandlerRegistry {
+        let address = self.server.server_addr().to_ip().expect("No ip");
+        let headers = match token {
+            Some(token) => format!("headers = {{ X-Consul-Token = \"{token}\" }}"),
+            None => String::new(),
+        };
+        let config: ProtocolHandlerConfig =
+            toml::from_str(&format!("[http.hosts.\"{}\"]\n{headers}", address.ip()))
+                .expect("Could not parse config");
+        ProtocolHandlerRegistry::new(&config)
+    }
+}
+
+impl Drop for ConsulTestServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+fn respond(request: Request, status: u16, body: impl Into<Vec<u8>>) {
+    let _ = request.respond(Response::from_data(body.into()).with_status_code(status));
+}
+
+fn handle_request(mut request: Request, entries: &Entries) {
+    let token = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("X-Consul-Token"))
+        .map(|header| header.value.to_string());
+    if token.as_deref() != Some(TOKEN) {
+        return respond(request, 403, "ACL not found");
+    }
+
+    let url = Url::parse(&format!("http://localhost{}", request.url())).expect("Bad url");
+    let Some(key) = url.path().strip_prefix("/v1/kv/") else {
+        return respond(request, 404, "");
+    };
+    let key = percent_decode_str(key).decode_utf8_lossy().to_string();
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let mut body = Vec::new();
+    let _ = request.as_reader().read_to_end(&mut body);
+
+    let mut guard = entries.lock().expect("Could not lock entries");
+    let (entries, next_index) = &mut *guard;
+    match (request.method(), query.contains_key("keys")) {
+        (Method::Get, true) => {
+            let mut keys: Vec<String> = Vec::new();
+            for stored in entries.keys().filter(|stored| stored.starts_with(&key)) {
+                let rest = &stored[key.len()..];
+                let listed = match (query.contains_key("separator"), rest.split_once('/')) {
+                    (true, Some((container, _))) => format!("{key}{container}/"),
+                    _ => stored.clone(),
+                };
+                if !keys.contains(&listed) {
+                    keys.push(listed);
+                }
+            }
+            match keys.is_empty() {
+                true => respond(request, 404, ""),
+                false => respond(request, 200, serde_json::to_vec(&keys).expect("Bad keys")),
+            }
+        }
+        (Method::Get, false) => match entries.get(&key) {
+            None => respond(request, 404, ""),
+            Some((value, _)) if query.contains_key("raw") => respond(request, 200, value.clone()),
+            Some((value, index)) => {
+                let entry = serde_json::json!([{
+                    "Key": key,
+                    "Value": STANDARD.encode(value),
+                    "ModifyIndex": index,
+                }]);
+                respond(request, 200, entry.to_string())
+            }
+        },
+        (Method::Put, _) => {
+            let current = entries.get(&key).map_or(0, |(_, index)| *index);
+            let cas = query
+                .get("cas")
+                .map(|cas| cas.parse::<u64>().expect("Bad cas"));
+            if cas.is_some_and(|cas| cas != current) {
+                return respond(request, 200, "false");
+            }
+            entries.insert(key, (body, *next_index));
+            *next_index += 1;
+            respond(request, 200, "true")
+        }
+        (Method::Delete, _) if query.contains_key("recurse") => {
+            entries.retain(|stored, _| !stored.starts_with(&key));
+            respond(request, 200, "true")
+        }
+        (Method::Delete, _) => {
+            entries.remove(&key);
+            respond(request, 200, "true")
+        }
+        _ => respond(request, 405, ""),
+    }
+}
+
+#[test]
+fn records_can_be_stored_in_consul() {
+    let server = ConsulTestServer::start();
+    let url = server.url("/kv/services/app.toml");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = server.build_registry(Some(TOKEN));
+    let good_record = NestedStruct::build_struct_with_items();
+
+    push_record_to_url(&url, &good_record, &protocol_handlers, &format_handlers)
+        .expect("Could not push record");
+    let candidate: NestedStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+    assert!(server.get("services/app.toml").is_some());
+
+    let updated: NestedStruct = update_record_at_url(
+        &url,
+        |record: &mut NestedStruct| {
+            record.map.clear();
+            Ok(())
+        },
+        &protocol_handlers,
+        &format_handlers,
+    )
+    .expect("Could not update record");
+    assert!(updated.map.is_empty());
+
+    delete_string_from_url(&url, &protocol_handlers).expect("Could not delete record");
+    let record = fetch_string_from_url(&url, &protocol_handlers).expect("Could not fetch record");
+    assert_eq!(None, record);
+
+    let unauthenticated = server.build_registry(None);
+    assert!(push_string_to_url(&url, "Foo", &unauthenticated).is_err());
+}
+
+#[test]
+fn consul_push_checks_modify_index() {
+    let server = ConsulTestServer::start();
+    let url = server.url("/kv/record");
+    let registry = server.build_registry(Some(TOKEN));
+
+    push_string_to_url_if_version(&url, "Foo", None, &registry).expect("Could not create record");
+    let result = push_string_to_url_if_version(&url, "Bar", None, &registry);
+    assert!(result.is_err_and(|error| error.is::<ConflictError>()));
+
+    let (record, version) =
+        fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foo".to_string()), record);
+    assert!(matches!(version, Some(UrlVersion::Index(_))));
+
+    push_string_to_url_if_version(&url, "Foobar", version.as_ref(), &registry)
+        .expect("Could not push record");
+    let error = push_string_to_url_if_version(&url, "Barfoo", version.as_ref(), &registry)
+        .expect_err("Push should conflict");
+    let conflict = error
+        .downcast_ref::<ConflictError>()
+        .expect("Not a ConflictError");
+    let (_, current) = fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    assert_eq!(current.as_ref(), conflict.found());
+    assert_ne!(version.as_ref(), conflict.found());
+    assert_eq!(Some(b"Foobar".to_vec()), server.get("record"));
+}
+
+#[test]
+fn consul_ignores_configured_http_methods() {
+    let server = ConsulTestServer::start();
+    let url = server.url("/kv/record");
+    let address = server.server.server_addr().to_ip().expect("No ip");
+    let config: ProtocolHandlerConfig = toml::from_str(&format!(
+        r#"
+        [http.hosts."{}"]
+        headers = {{ X-Consul-Token = "{TOKEN}" }}
+        fetch_method = "Post"
+        push_method = "Post"
+        "#,
+        address.ip()
+    ))
+    .expect("Could not parse config");
+    let registry = ProtocolHandlerRegistry::new(&config);
+
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foo".to_string()), record);
+}
+
+#[test]
+fn consul_containers_are_key_prefixes() {
+    let server = ConsulTestServer::start();
+    let registry = server.build_registry(Some(TOKEN));
+    for key in [
+        "a.toml",
+        "apps/b.toml",
+        "apps/nested/c.toml",
+        "apps_other.toml",
+    ] {
+        let url = server.url(&format!("/kv/configs/{key}"));
+        push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    }
+
+    let configs = server.url("/kv/configs");
+    let children = list_urls_in_url_container(&configs, &registry).expect("Could not list keys");
+    let expected: HashSet<Url> = ["a.toml", "apps/", "apps_other.toml"]
+        .iter()
+        .map(|child| server.url(&format!("/kv/configs/{child}")))
+        .collect();
+    assert_eq!(expected, children);
+
+    let apps = server.url("/kv/configs/apps/");
+    let metadata = stat_url(&apps, &registry).expect("Could not stat prefix");
+    assert!(metadata.exists && metadata.is_container);
+    let metadata =
+        stat_url(&server.url("/kv/configs/a.toml"), &registry).expect("Could not stat key");
+    assert!(metadata.exists && !metadata.is_container);
+    assert_eq!(Some(3), metadata.size);
+
+    assert!(delete_url_container(&apps, false, &registry).is_err());
+    delete_url_container(&apps, true, &registry).expect("Could not delete prefix");
+    assert_eq!(None, server.get("configs/apps/nested/c.toml"));
+    assert!(server.get("configs/apps_other.toml").is_some());
+    assert!(!url_exists(&apps, &registry).expect("Could not stat prefix"));
+}
@@ -0,0 +1,97 @@
+use super::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use url::Url;
+
+#[test]
+fn string_can_be_fetched_from_percent_encoded_data_url() {
+    let url = Url::parse("data:,Foo%20bar").expect("Could not build url");
+
+    let handler = DataProtocolHandler::default();
+    let record = handler
+        .fetch_string_from_url(&url)
+        .expect("Could not fetch record");
+
+    assert_eq!(Some("Foo bar".to_string()), record);
+}
+
+#[test]
+fn string_can_be_fetched_from_base64_data_url() {
+    let payload: String = "Foobar".to_string();
+    let url = Url::parse(&format!("data:text/plain;base64,{}", STANDARD.encode(&payload)))
+        .expect("Could not build url");
+
+    let handler = DataProtocolHandler::default();
+    let record = handler
+        .fetch_string_from_url(&url)
+        .expect("Could not fetch record");
+
+    assert_eq!(Some(payload), record);
+}
+
+#[test]
+fn media_type_is_parsed_from_data_url() {
+    let url = Url::parse("data:Application/JSON;charset=utf-8;base64,e30=")
+        .expect("Could not build url");
+    let data_url = DataUrl::try_from_url(&url).expect("Could not parse data url");
+    assert_eq!("application/json", data_url.media_type());
+    assert_eq!(b"{}", data_url.data());
+
+    let url = Url::parse("data:,{}").expect("Could not build url");
+    let data_url = DataUrl::try_from_url(&url).expect("Could not parse data url");
+    assert_eq!("text/plain", data_url.media_type());
+}
+
+#[test]
+fn data_url_without_separator_is_rejected() {
+    let url = Url::parse("data:text/plain;base64").expect("Could not build url");
+
+    let handler = DataProtocolHandler::default();
+    assert!(handler.fetch_string_from_url(&url).is_err());
+}
+
+#[test]
+fn data_url_can_not_be_pushed_to() {
+    let url = Url::parse("data:,Foobar").expect("Could not build url");
+
+    let handler = DataProtocolHandler::default();
+    assert!(handler.push_string_to_url(&url, "Barfoo").is_err());
+    assert!(handler.delete_string_from_url(&url).is_err());
+}
+
+#[test]
+fn struct_can_be_built_from_json_data_url() {
+    let good_record = TestStruct::build_foo();
+    let string = serde_json::to_string(&good_record).expect("Could not serialize record");
+    let url = Url::parse(&format!("data:application/json;base64,{}", STANDARD.encode(string)))
+        .expect("Could not build url");
+
+    let candidate: TestStruct = build_record_from_url(
+        &url,
+        &ProtocolHandlerRegistry::default(),
+        &FormatHandlerRegistry::default(),
+    )
+    .expect("Could not parse record");
+
+    assert_eq!(good_record, candidate);
+}
+
+#[test]
+fn struct_can_be_built_from_toml_data_url() {
+    let good_record = TestStruct::build_foo();
+    let string = toml::to_string(&good_record).expect("Could not serialize record");
+    let payload: String = percent_encoding::utf8_percent_encode(
+        &string,
+        percent_encoding::NON_ALPHANUMERIC,
+    )
+    .to_string();
+    let url = Url::parse(&format!("data:application/toml,{payload}")).expect("Could not build url");
+
+    let candidate: TestStruct = build_record_from_url(
+        &url,
+        &ProtocolHandlerRegistry::default(),
+        &FormatHandlerRegistry::default(),
+    )
+    .expect("Could not parse record");
+
+    assert_eq!(good_record, candidate);
+}
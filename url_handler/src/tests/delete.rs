@@ -0,0 +1,87 @@
+use super::http::TestServer;
+use super::*;
+use std::fs::{create_dir_all, write};
+use tempfile::TempDir;
+
+#[test]
+fn file_container_is_only_deleted_recursively_when_asked() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let container = tmp_dir.path().join("container");
+    create_dir_all(container.join("nested")).expect("Could not create directory");
+    write(container.join("nested/a.toml"), "Foo").expect("Could not write file");
+    let url = try_build_url_from_path_buf(&container).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    assert!(delete_url_container(&url, false, &registry).is_err());
+    assert!(container.join("nested/a.toml").exists());
+
+    delete_url_container(&url, true, &registry).expect("Could not delete container");
+    assert!(!container.exists());
+}
+
+#[test]
+fn file_root_is_never_deleted() {
+    let url = Url::parse("file:///").expect("Could not parse url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let error = delete_url_container(&url, false, &registry).expect_err("Root was deleted");
+    assert!(
+        error.to_string().contains("protected"),
+        "Unexpected error: {error}"
+    );
+}
+
+#[test]
+fn http_collection_can_be_deleted() {
+    let server = TestServer::start();
+    let registry = ProtocolHandlerRegistry::default();
+    server.put("/collection/a.json", b"{}");
+    server.put("/collection/b.json", b"{}");
+    server.put("/other.json", b"{}");
+
+    delete_url_container(&server.url("/collection"), true, &registry)
+        .expect("Could not delete collection");
+
+    assert_eq!(None, server.get("/collection/a.json"));
+    assert_eq!(None, server.get("/collection/b.json"));
+    assert_eq!(Some(b"{}".to_vec()), server.get("/other.json"));
+    assert!(delete_url_container(&server.url("/"), true, &registry).is_err());
+}
+
+#[test]
+fn archive_container_can_be_deleted() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.zip"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    let member = |path: &str| Url::parse(&format!("zip+{archive}!/{path}")).expect("Bad url");
+    create_url_container(&member(""), &registry).expect("Could not create archive");
+    push_string_to_url(&member("conf/a.toml"), "Foo", &registry).expect("Could not push");
+    push_string_to_url(&member("app.toml"), "Bar", &registry).expect("Could not push");
+
+    assert!(delete_url_container(&member("conf"), false, &registry).is_err());
+    delete_url_container(&member("conf"), true, &registry).expect("Could not delete container");
+
+    assert!(!url_exists(&member("conf/a.toml"), &registry).expect("Could not stat url"));
+    assert!(url_exists(&member("app.toml"), &registry).expect("Could not stat url"));
+}
+
+#[test]
+fn container_can_be_moved_across_protocols() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let source = tmp_dir.path().join("source");
+    create_dir_all(&source).expect("Could not create directory");
+    write(source.join("a.toml"), "Foo").expect("Could not write file");
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.zip"))
+        .expect("Could not build url");
+    let target = Url::parse(&format!("zip+{archive}!/")).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let source_url = try_build_url_from_path_buf(&source).expect("Could not build url");
+    move_url(&source_url, &target, &registry).expect("Could not move container");
+
+    assert!(!source.exists());
+    let member = Url::parse(&format!("zip+{archive}!/a.toml")).expect("Bad url");
+    let record = fetch_string_from_url(&member, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foo".to_string()), record);
+}
@@ -0,0 +1,138 @@
+use super::*;
+use age::secrecy::ExposeSecret;
+use std::fs::{read, write};
+use tempfile::TempDir;
+use url::Url;
+
+fn build_registry(config: &str) -> ProtocolHandlerRegistry {
+    let config: ProtocolHandlerConfig = toml::from_str(config).expect("Could not parse config");
+    ProtocolHandlerRegistry::new(&config)
+}
+
+fn build_key_pair() -> (String, String) {
+    let identity = age::x25519::Identity::generate();
+    let recipient = identity.to_public().to_string();
+    (recipient, identity.to_string().expose_secret().to_string())
+}
+
+fn assert_record_round_trip(url: &Url, registry: &ProtocolHandlerRegistry) {
+    let format_handlers = FormatHandlerRegistry::default();
+    let good_record = NestedStruct::build_struct_with_items();
+
+    push_record_to_url(url, &good_record, registry, &format_handlers)
+        .expect("Could not push record");
+    let candidate: NestedStruct =
+        build_record_from_url(url, registry, &format_handlers).expect("Could not parse record");
+
+    assert_eq!(good_record, candidate);
+}
+
+#[test]
+fn struct_can_be_stored_encrypted_to_recipient() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test.toml.age");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let (recipient, identity) = build_key_pair();
+    let registry = build_registry(&format!(
+        r#"
+        [http]
+        [encryption]
+        recipients = ["{recipient}"]
+        identities = ["{identity}"]
+        "#
+    ));
+
+    assert_record_round_trip(&url, &registry);
+
+    let bytes = read(&target_file).expect("Could not read file");
+    assert!(bytes.starts_with(b"age-encryption.org/v1"));
+}
+
+#[test]
+fn struct_can_be_stored_encrypted_with_passphrase_and_compression() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url = try_build_url_from_path_buf(&tmp_dir.path().join("test.json.gz.age"))
+        .expect("Could not build url");
+    let registry = build_registry(
+        r#"
+        [http]
+        [encryption]
+        passphrase = "correct horse battery staple"
+        work_factor = 10
+        "#,
+    );
+
+    assert_record_round_trip(&url, &registry);
+}
+
+#[test]
+fn urls_below_configured_prefix_are_encrypted() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let secrets =
+        try_build_url_from_path_buf(&tmp_dir.path().join("secrets")).expect("Could not build url");
+    let target_file = tmp_dir.path().join("secrets").join("test.toml");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let (recipient, identity) = build_key_pair();
+    let registry = build_registry(&format!(
+        r#"
+        [http]
+        [encryption]
+        recipients = ["{recipient}"]
+        identities = ["{identity}"]
+        prefixes = ["{secrets}/"]
+        "#
+    ));
+
+    assert_record_round_trip(&url, &registry);
+
+    let bytes = read(&target_file).expect("Could not read file");
+    assert!(bytes.starts_with(b"age-encryption.org/v1"));
+}
+
+#[test]
+fn struct_can_be_decrypted_with_identity_file() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url = try_build_url_from_path_buf(&tmp_dir.path().join("test.toml.age"))
+        .expect("Could not build url");
+    let identity_file = tmp_dir.path().join("identity.txt");
+    let (recipient, identity) = build_key_pair();
+    write(&identity_file, format!("# test key\n{identity}\n")).expect("Could not write identity");
+    let registry = build_registry(&format!(
+        r#"
+        [http]
+        [encryption]
+        recipients = ["{recipient}"]
+        identity_files = ["{}"]
+        "#,
+        identity_file.display()
+    ));
+
+    assert_record_round_trip(&url, &registry);
+}
+
+#[test]
+fn encrypted_record_can_not_be_read_without_identity() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url = try_build_url_from_path_buf(&tmp_dir.path().join("test.toml.age"))
+        .expect("Could not build url");
+    let (recipient, _) = build_key_pair();
+    let (_, other_identity) = build_key_pair();
+    let format_handlers = FormatHandlerRegistry::default();
+
+    let registry = build_registry(&format!(
+        r#"
+        [http]
+        [encryption]
+        recipients = ["{recipient}"]
+        identities = ["{other_identity}"]
+        "#
+    ));
+    push_record_to_url(&url, &TestStruct::build_foo(), &registry, &format_handlers)
+        .expect("Could not push record");
+
+    let candidate: Result<TestStruct> = build_record_from_url(&url, &registry, &format_handlers);
+    assert!(candidate.is_err());
+    let candidate: Result<TestStruct> =
+        build_record_from_url(&url, &ProtocolHandlerRegistry::default(), &format_handlers);
+    assert!(candidate.is_err());
+}
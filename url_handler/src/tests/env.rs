@@ -0,0 +1,55 @@
+use super::*;
+use std::env::set_var;
+use url::Url;
+
+#[test]
+fn string_can_be_fetched_from_env() {
+    let payload: String = "Foobar".to_string();
+    set_var("URL_HANDLER_TEST_FETCH_STRING", &payload);
+    let url = Url::parse("env:URL_HANDLER_TEST_FETCH_STRING").expect("Could not build url");
+
+    let handler = EnvProtocolHandler::default();
+    let record = handler
+        .fetch_string_from_url(&url)
+        .expect("Could not fetch record");
+
+    assert_eq!(Some(payload), record);
+}
+
+#[test]
+fn missing_env_variable_is_empty() {
+    let url = Url::parse("env:URL_HANDLER_TEST_MISSING_VARIABLE").expect("Could not build url");
+
+    let handler = EnvProtocolHandler::default();
+    let record = handler
+        .fetch_string_from_url(&url)
+        .expect("Could not fetch record");
+
+    assert_eq!(None, record);
+}
+
+#[test]
+fn env_can_not_be_pushed_to() {
+    let url = Url::parse("env:URL_HANDLER_TEST_PUSH_STRING").expect("Could not build url");
+
+    let handler = EnvProtocolHandler::default();
+    assert!(handler.push_string_to_url(&url, "Foobar").is_err());
+    assert!(handler.delete_string_from_url(&url).is_err());
+}
+
+#[test]
+fn struct_can_be_built_from_env() {
+    let good_record = TestStruct::build_foo();
+    let string = toml::to_string(&good_record).expect("Could not serialize record");
+    set_var("URL_HANDLER_TEST_FETCH_RECORD", string);
+    let url = Url::parse("env:URL_HANDLER_TEST_FETCH_RECORD").expect("Could not build url");
+
+    let candidate: TestStruct = build_record_from_url(
+        &url,
+        &ProtocolHandlerRegistry::default(),
+        &FormatHandlerRegistry::default(),
+    )
+    .expect("Could not parse record");
+
+    assert_eq!(good_record, candidate);
+}
@@ -0,0 +1,115 @@
+use super::*;
+use std::fs::read_to_string;
+use tempfile::TempDir;
+use url::Url;
+
+fn build_registry(config: &str) -> ProtocolHandlerRegistry {
+    let config: ProtocolHandlerConfig = toml::from_str(config).expect("Could not parse config");
+    ProtocolHandlerRegistry::new(&config)
+}
+
+#[test]
+fn string_can_be_fetched_from_command() {
+    let registry = build_registry(
+        r#"
+        [http]
+        [exec]
+        enabled = true
+        [exec.commands.greeting]
+        fetch = "printf 'Foo bar'"
+        "#,
+    );
+    let url = Url::parse("exec:greeting").expect("Could not build url");
+
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+
+    assert_eq!(Some("Foo bar".to_string()), record);
+}
+
+#[test]
+fn string_can_be_pushed_to_command() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test_push_record");
+    let registry = build_registry(&format!(
+        r#"
+        [http]
+        [exec]
+        enabled = true
+        [exec.commands.store]
+        push = "sh -c 'cat > \"$0\"' '{}'"
+        "#,
+        target_file.display()
+    ));
+    let url = Url::parse("exec:store").expect("Could not build url");
+    let payload: String = "Foobar".to_string();
+
+    push_string_to_url(&url, &payload, &registry).expect("Could not push record");
+
+    let record = read_to_string(target_file).expect("Could not read string from file");
+    assert_eq!(payload, record);
+}
+
+#[test]
+fn exec_handler_is_disabled_by_default() {
+    let registry = build_registry(
+        r#"
+        [http]
+        [exec.commands.greeting]
+        fetch = "printf 'Foo bar'"
+        "#,
+    );
+    let url = Url::parse("exec:greeting").expect("Could not build url");
+
+    assert!(fetch_string_from_url(&url, &registry).is_err());
+    assert!(fetch_string_from_url(&url, &ProtocolHandlerRegistry::default()).is_err());
+}
+
+#[test]
+fn unknown_commands_are_rejected() {
+    let registry = build_registry(
+        r#"
+        [http]
+        [exec]
+        enabled = true
+        "#,
+    );
+    let url = Url::parse("exec:echo%20Foobar").expect("Could not build url");
+
+    assert!(fetch_string_from_url(&url, &registry).is_err());
+}
+
+#[test]
+fn failing_command_is_an_error() {
+    let registry = build_registry(
+        r#"
+        [http]
+        [exec]
+        enabled = true
+        [exec.commands.broken]
+        fetch = "sh -c 'echo Foobar; exit 3'"
+        "#,
+    );
+    let url = Url::parse("exec:broken").expect("Could not build url");
+
+    assert!(fetch_string_from_url(&url, &registry).is_err());
+}
+
+#[test]
+fn slow_command_times_out() {
+    let registry = build_registry(
+        r#"
+        [http]
+        [exec]
+        enabled = true
+        timeout = "10s"
+        [exec.commands.slow]
+        fetch = "sleep 5"
+        timeout = "100ms"
+        "#,
+    );
+    let url = Url::parse("exec:slow").expect("Could not build url");
+
+    let start = std::time::Instant::now();
+    assert!(fetch_string_from_url(&url, &registry).is_err());
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
@@ -163,3 +163,100 @@ fn urls_can_be_discovered_from_container() {
     assert!(candidate.contains(&good_url));
     assert!(!candidate.contains(&bad_url));
 }
+
+#[test]
+fn push_replaces_file_without_leaving_temporary_files() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test_atomic_record");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+
+    let handler = FileProtocolHandler::default();
+    handler
+        .push_string_to_url(&url, "Foo")
+        .expect("Could not push record");
+    handler
+        .push_string_to_url(&url, "Foobar")
+        .expect("Could not push record");
+
+    let record: String = read_to_string(&target_file).expect("Could not read string from file");
+    assert_eq!("Foobar", record);
+
+    let entries: Vec<_> = tmp_dir
+        .path()
+        .read_dir()
+        .expect("Could not read dir")
+        .collect();
+    assert_eq!(1, entries.len());
+}
+
+#[cfg(unix)]
+#[test]
+fn push_preserves_existing_permissions() {
+    use std::fs::{metadata, set_permissions, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test_permission_record");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    write(&target_file, "Foo").expect("Could not write payload to file");
+    set_permissions(&target_file, Permissions::from_mode(0o640))
+        .expect("Could not set permissions");
+
+    let handler = FileProtocolHandler::default();
+    handler
+        .push_string_to_url(&url, "Foobar")
+        .expect("Could not push record");
+
+    let mode = metadata(&target_file)
+        .expect("Could not read metadata")
+        .permissions()
+        .mode();
+    assert_eq!(0o640, mode & 0o777);
+}
+
+#[cfg(unix)]
+#[test]
+fn push_writes_through_symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test_target_record");
+    let link = tmp_dir.path().join("test_link_record");
+    write(&target_file, "Foo").expect("Could not write payload to file");
+    symlink("test_target_record", &link).expect("Could not create symlink");
+    let url = try_build_url_from_path_buf(&link).expect("Could not build url");
+
+    let handler = FileProtocolHandler::default();
+    handler
+        .push_string_to_url(&url, "Foobar")
+        .expect("Could not push record");
+
+    assert!(link
+        .symlink_metadata()
+        .expect("Could not read metadata")
+        .file_type()
+        .is_symlink());
+    let record: String = read_to_string(&target_file).expect("Could not read string from file");
+    assert_eq!("Foobar", record);
+}
+
+#[test]
+fn push_can_sync_parent_directory() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("nested").join("test_sync_record");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let config: ProtocolHandlerConfig = toml::from_str(
+        r#"
+        [file]
+        sync_parent_directory = true
+        [http]
+        "#,
+    )
+    .expect("Could not parse config");
+    let registry = ProtocolHandlerRegistry::new(&config);
+
+    push_string_to_url(&url, "Foobar", &registry).expect("Could not push record");
+
+    let record: String = read_to_string(&target_file).expect("Could not read string from file");
+    assert_eq!("Foobar", record);
+}
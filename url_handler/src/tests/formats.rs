@@ -111,3 +111,15 @@ fn nested_struct_with_items_can_be_stored_in_json() {
     let bad_record = NestedStruct::build_struct_without_items();
     assert_ne!(bad_record, candidate);
 }
+
+#[test]
+fn json_records_are_written_as_json() {
+    let good_record = NestedStruct::build_struct_with_items();
+    let handlers = FormatHandlerRegistry::new();
+    let string = build_string_from_record_with_extension(&good_record, "json", &handlers)
+        .expect("Could not serialize record");
+    let candidate: NestedStruct =
+        serde_json::from_str(&string).expect("Record was not serialized as json");
+    assert_eq!(good_record, candidate);
+    assert!(toml::from_str::<NestedStruct>(&string).is_err());
+}
@@ -0,0 +1,128 @@
+use super::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(repository: &Path, arguments: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(arguments)
+        .output()
+        .expect("Could not run git");
+    assert!(output.status.success(), "git {arguments:?} failed");
+    String::from_utf8(output.stdout).expect("Output is not utf8")
+}
+
+fn build_registry(extra: &str) -> ProtocolHandlerRegistry {
+    let config: ProtocolHandlerConfig = toml::from_str(&format!(
+        r#"
+        [http]
+        [git]
+        author_name = "Config Bot"
+        author_email = "bot@example.com"
+        message = "{{action}} {{path}} via url_handler"
+        {extra}
+        "#
+    ))
+    .expect("Could not parse config");
+    ProtocolHandlerRegistry::new(&config)
+}
+
+fn build_git_url(path: &Path, suffix: &str) -> Url {
+    let url = try_build_url_from_path_buf(&path.to_path_buf()).expect("Could not build url");
+    Url::parse(&format!("git+{url}{suffix}")).expect("Could not build git url")
+}
+
+#[test]
+fn push_and_delete_are_committed() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let repository = tmp_dir.path();
+    git(repository, &["init", "-q", "-b", "main"]);
+    let url = build_git_url(&repository.join("app.toml"), "");
+    let registry = build_registry("");
+
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    push_string_to_url(&url, "Bar", &registry).expect("Could not push string");
+
+    let log = git(repository, &["log", "--format=%an <%ae>|%s"]);
+    assert_eq!(
+        vec![
+            "Config Bot <bot@example.com>|Update app.toml via url_handler",
+            "Config Bot <bot@example.com>|Update app.toml via url_handler",
+        ],
+        log.lines().collect::<Vec<_>>()
+    );
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Bar".to_string()), record);
+
+    delete_string_from_url(&url, &registry).expect("Could not delete string");
+    let log = git(repository, &["log", "-1", "--format=%s"]);
+    assert_eq!("Delete app.toml via url_handler", log.trim());
+}
+
+#[test]
+fn records_can_be_pushed_into_new_directories() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let repository = tmp_dir.path();
+    git(repository, &["init", "-q", "-b", "main"]);
+    let registry = build_registry("");
+    let url = build_git_url(&repository.join("new/dir/rec.toml"), "");
+
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+
+    let files = git(repository, &["ls-files"]);
+    assert_eq!("new/dir/rec.toml", files.trim());
+    let log = git(repository, &["log", "-1", "--format=%s"]);
+    assert_eq!("Update new/dir/rec.toml via url_handler", log.trim());
+}
+
+#[test]
+fn records_can_be_read_at_revision() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let repository = tmp_dir.path();
+    git(repository, &["init", "-q", "-b", "main"]);
+    let registry = build_registry("");
+    let url = build_git_url(&repository.join("conf/app.toml"), "");
+    create_url_container(&build_git_url(&repository.join("conf"), ""), &registry)
+        .expect("Could not create container");
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    push_string_to_url(&url, "Bar", &registry).expect("Could not push string");
+
+    let url = build_git_url(&repository.join("conf/app.toml"), "?ref=HEAD~1");
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foo".to_string()), record);
+    assert!(push_string_to_url(&url, "Foobar", &registry).is_err());
+
+    let root = build_git_url(repository, "#HEAD");
+    let urls = list_urls_in_url_container(&root, &registry).expect("Could not list urls");
+    assert_eq!(
+        HashSet::from([build_git_url(&repository.join("conf"), "/#HEAD")]),
+        urls
+    );
+}
+
+#[test]
+fn commits_are_pushed_to_remote() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let repository = tmp_dir.path().join("work");
+    let remote = tmp_dir.path().join("remote.git");
+    std::fs::create_dir_all(&repository).expect("Could not create directory");
+    git(
+        tmp_dir.path(),
+        &["init", "-q", "--bare", "-b", "main", "remote.git"],
+    );
+    git(&repository, &["init", "-q", "-b", "main"]);
+    git(
+        &repository,
+        &["remote", "add", "origin", &remote.to_string_lossy()],
+    );
+    let registry = build_registry("push = true\nbranch = \"main\"");
+
+    let url = build_git_url(&repository.join("app.toml"), "");
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+
+    let log = git(&remote, &["log", "-1", "--format=%s", "main"]);
+    assert_eq!("Update app.toml via url_handler", log.trim());
+}
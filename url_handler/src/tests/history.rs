@@ -0,0 +1,144 @@
+use super::*;
+use std::fs::read_to_string;
+use tempfile::TempDir;
+
+fn build_registry(keep: usize) -> ProtocolHandlerRegistry {
+    let config: ProtocolHandlerConfig = toml::from_str(&format!(
+        r#"
+        [http]
+        [history]
+        enabled = true
+        keep = {keep}
+        "#
+    ))
+    .expect("Could not parse config");
+    ProtocolHandlerRegistry::new(&config)
+}
+
+#[test]
+fn overwritten_records_are_kept_as_versions() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test.toml");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let protocol_handlers = build_registry(2);
+    let format_handlers = FormatHandlerRegistry::default();
+
+    for id in 0..4 {
+        let record = TestStruct {
+            id,
+            ..TestStruct::build_foo()
+        };
+        push_record_to_url(&url, &record, &protocol_handlers, &format_handlers)
+            .expect("Could not push record");
+    }
+
+    let versions = list_versions(&url, &protocol_handlers).expect("Could not list versions");
+    assert_eq!(
+        vec![1, 2],
+        versions
+            .iter()
+            .map(|version| version.number)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        tmp_dir.path().join(".history/test.toml.~1~"),
+        versions[0]
+            .url
+            .to_file_path()
+            .expect("Could not build path")
+    );
+
+    let bytes = fetch_version(&url, 2, &protocol_handlers)
+        .expect("Could not fetch version")
+        .expect("Version is empty");
+    let record: TestStruct =
+        toml::from_str(&String::from_utf8(bytes).expect("Not utf8")).expect("Not a record");
+    assert_eq!(1, record.id);
+    assert!(fetch_version(&url, 3, &protocol_handlers).is_err());
+}
+
+#[test]
+fn record_can_be_rolled_back() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test.txt");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let registry = build_registry(5);
+
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    push_string_to_url(&url, "Bar", &registry).expect("Could not push string");
+    let versions = list_versions(&url, &registry).expect("Could not list versions");
+    assert_eq!(1, versions.len());
+
+    // The rollback is a push as well and keeps the content it replaced.
+    rollback(&url, 1, &registry).expect("Could not roll back");
+    assert_eq!(
+        "Foo",
+        read_to_string(&target_file).expect("Could not read file")
+    );
+
+    let bytes = fetch_version(&url, 1, &registry)
+        .expect("Could not fetch version")
+        .expect("Version is empty");
+    assert_eq!(b"Bar".to_vec(), bytes);
+}
+
+#[test]
+fn history_is_not_kept_by_default() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url =
+        try_build_url_from_path_buf(&tmp_dir.path().join("test.txt")).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    push_string_to_url(&url, "Bar", &registry).expect("Could not push string");
+
+    assert!(!tmp_dir.path().join(".history").exists());
+}
+
+#[test]
+fn history_is_only_kept_for_successful_pushes() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test.txt");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let registry = build_registry(5);
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    let (_, version) = fetch_string_with_version(&url, &registry).expect("Could not fetch");
+    push_string_to_url(&url, "Bar", &registry).expect("Could not push string");
+
+    assert!(push_string_to_url_if_version(&url, "Baz", version.as_ref(), &registry).is_err());
+
+    let versions = list_versions(&url, &registry).expect("Could not list versions");
+    assert_eq!(1, versions.len());
+    let bytes = fetch_version(&url, 1, &registry)
+        .expect("Could not fetch version")
+        .expect("Version is empty");
+    assert_eq!(b"Foo".to_vec(), bytes);
+}
+
+#[test]
+fn stdio_pushes_work_with_history_enabled() {
+    let url = Url::parse("stdio:").expect("Could not build url");
+    let registry = build_registry(5);
+
+    push_string_to_url(&url, "", &registry).expect("Could not push string");
+}
+
+#[test]
+fn updates_keep_the_replaced_content() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url = try_build_url_from_path_buf(&tmp_dir.path().join("test.txt"))
+        .expect("Could not build url");
+    let registry = build_registry(5);
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+
+    update_bytes_at_url(&url, &mut |_| Ok(b"Bar".to_vec()), &registry)
+        .expect("Could not update record");
+    assert!(update_bytes_at_url(&url, &mut |_| anyhow::bail!("Abort update"), &registry).is_err());
+
+    let versions = list_versions(&url, &registry).expect("Could not list versions");
+    assert_eq!(1, versions.len());
+    let bytes = fetch_version(&url, 1, &registry)
+        .expect("Could not fetch version")
+        .expect("Version is empty");
+    assert_eq!(b"Foo".to_vec(), bytes);
+}
@@ -0,0 +1,197 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server};
+use url::Url;
+
+use super::try_build_url_from_unix_socket;
+
+type Records = Arc<Mutex<HashMap<String, (Vec<u8>, u64)>>>;
+
+pub struct TestServer {
+    server: Arc<Server>,
+    records: Records,
+}
+
+impl TestServer {
+    pub fn start() -> TestServer {
+        let server = Server::http("127.0.0.1:0").expect("Could not start server");
+        TestServer::serve(server, None, true)
+    }
+
+    // Versions records with Last-Modified dates only, like plain file servers.
+    pub fn start_without_etags() -> TestServer {
+        let server = Server::http("127.0.0.1:0").expect("Could not start server");
+        TestServer::serve(server, None, false)
+    }
+
+    // Requests without the bearer token are rejected when one is given.
+    pub fn start_unix(socket: &Path, bearer: Option<&str>) -> TestServer {
+        let server = Server::http_unix(socket).expect("Could not start server");
+        TestServer::serve(server, bearer.map(|token| format!("Bearer {token}")), true)
+    }
+
+    fn serve(server: Server, authorization: Option<String>, etags: bool) -> TestServer {
+        let server = Arc::new(server);
+        let records: Records = Arc::default();
+
+        let thread_server = server.clone();
+        let thread_records = records.clone();
+        thread::spawn(move || {
+            for request in thread_server.incoming_requests() {
+                if authorization.is_some()
+                    && get_header(&request, "Authorization") != authorization
+                {
+                    let _ = request.respond(Response::empty(401));
+                    continue;
+                }
+                handle_request(request, &thread_records, etags);
+            }
+        });
+        TestServer { server, records }
+    }
+
+    pub fn url(&self, path: &str) -> Url {
+        if let Some(address) = self.server.server_addr().to_unix() {
+            let socket = address.as_pathname().expect("Socket has no path");
+            return try_build_url_from_unix_socket(&socket.to_path_buf(), path)
+                .expect("Could not build url");
+        }
+        let address = self
+            .server
+            .server_addr()
+            .to_ip()
+            .expect("Server is not on ip");
+        Url::parse(&format!("http://{address}{path}")).expect("Could not build url")
+    }
+
+    pub fn get(&self, path: &str) -> Option<Vec<u8>> {
+        let records = self.records.lock().expect("Could not lock records");
+        records.get(path).map(|(bytes, _)| bytes.clone())
+    }
+
+    pub fn put(&self, path: &str, bytes: &[u8]) {
+        let mut records = self.records.lock().expect("Could not lock records");
+        let version = records.get(path).map_or(0, |(_, version)| version + 1);
+        records.insert(path.to_string(), (bytes.to_vec(), version));
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+fn get_header(request: &Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.to_string())
+}
+
+fn build_etag(version: u64) -> String {
+    format!("\"{version}\"")
+}
+
+// Every write moves the date forward by a second.
+fn build_last_modified(version: u64) -> DateTime<Utc> {
+    DateTime::from_timestamp(1_700_000_000 + version as i64, 0).expect("Bad timestamp")
+}
+
+fn build_version_header(version: u64, etags: bool) -> Header {
+    match etags {
+        true => Header::from_bytes("ETag", build_etag(version)),
+        false => Header::from_bytes(
+            "Last-Modified",
+            build_last_modified(version)
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string(),
+        ),
+    }
+    .expect("Bad header")
+}
+
+fn handle_request(mut request: Request, records: &Records, etags: bool) {
+    let path = request.url().to_string();
+    let mut body = Vec::new();
+    if request.as_reader().read_to_end(&mut body).is_err() {
+        let _ = request.respond(Response::empty(400));
+        return;
+    }
+    let if_match = get_header(&request, "If-Match");
+    let if_none_match = get_header(&request, "If-None-Match");
+    let if_unmodified_since = get_header(&request, "If-Unmodified-Since")
+        .and_then(|date| DateTime::parse_from_rfc2822(&date).ok());
+    let destination = get_header(&request, "Destination")
+        .and_then(|destination| Url::parse(&destination).ok())
+        .map(|destination| destination.path().to_string());
+
+    let mut records = records.lock().expect("Could not lock records");
+    let current = records.get(&path).cloned();
+    let response = match request.method() {
+        Method::Get | Method::Head => match current {
+            None => Response::from_data(Vec::new()).with_status_code(404),
+            Some((bytes, version)) => {
+                let bytes = match request.method() {
+                    Method::Head => Vec::new(),
+                    _ => bytes,
+                };
+                Response::from_data(bytes).with_header(build_version_header(version, etags))
+            }
+        },
+        Method::Put => {
+            let current_etag = current.as_ref().map(|(_, version)| build_etag(*version));
+            let current_modified = current
+                .as_ref()
+                .map(|(_, version)| build_last_modified(*version));
+            let precondition_failed = match (&if_match, &if_none_match, &if_unmodified_since) {
+                (Some(expected), _, _) => current_etag.as_ref() != Some(expected),
+                (_, Some(_), _) => current_etag.is_some(),
+                (_, _, Some(since)) => current_modified.is_none_or(|modified| modified > *since),
+                _ => false,
+            };
+            if precondition_failed {
+                Response::from_data(Vec::new()).with_status_code(412)
+            } else {
+                let version = current.map_or(0, |(_, version)| version + 1);
+                records.insert(path, (body, version));
+                Response::from_data(Vec::new())
+                    .with_status_code(204)
+                    .with_header(build_version_header(version, etags))
+            }
+        }
+        Method::Delete if path.ends_with('/') => {
+            let count = records.len();
+            records.retain(|record, _| !record.starts_with(&path));
+            match records.len() < count {
+                true => Response::from_data(Vec::new()).with_status_code(204),
+                false => Response::from_data(Vec::new()).with_status_code(404),
+            }
+        }
+        Method::Delete => match records.remove(&path) {
+            Some(_) => Response::from_data(Vec::new()).with_status_code(204),
+            None => Response::from_data(Vec::new()).with_status_code(404),
+        },
+        method if matches!(method.as_str(), "COPY" | "MOVE") => match (current, destination) {
+            (Some((bytes, _)), Some(destination)) => {
+                if method.as_str() == "MOVE" {
+                    records.remove(&path);
+                }
+                let version = records
+                    .get(&destination)
+                    .map_or(0, |(_, version)| version + 1);
+                records.insert(destination, (bytes, version));
+                Response::from_data(Vec::new()).with_status_code(201)
+            }
+            (None, _) => Response::from_data(Vec::new()).with_status_code(404),
+            (_, None) => Response::from_data(Vec::new()).with_status_code(400),
+        },
+        _ => Response::from_data(Vec::new()).with_status_code(405),
+    };
+    drop(records);
+    let _ = request.respond(response);
+}
@@ -0,0 +1,48 @@
+use super::*;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+// Answers a single request with an empty body and returns its method.
+fn serve_once() -> (Url, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind port");
+    let address = listener.local_addr().expect("Could not get address");
+    let url = Url::parse(&format!("http://{address}/app.toml")).expect("Could not build url");
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("Could not accept request");
+        let mut request_line = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut request_line)
+            .expect("Could not read request");
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .expect("Could not write response");
+        request_line
+            .split(' ')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    });
+    (url, server)
+}
+
+#[test]
+fn configured_methods_are_used_per_operation() {
+    let config: ProtocolHandlerConfig = toml::from_str(
+        r#"
+        [http.hosts."127.0.0.1"]
+        fetch_method = "Post"
+        push_method = "Patch"
+        "#,
+    )
+    .expect("Could not parse config");
+    let registry = ProtocolHandlerRegistry::new(&config);
+
+    let (url, server) = serve_once();
+    fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!("POST", server.join().expect("Server failed"));
+
+    let (url, server) = serve_once();
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push record");
+    assert_eq!("PATCH", server.join().expect("Server failed"));
+}
@@ -0,0 +1,77 @@
+use super::http::TestServer;
+use super::*;
+use crate::try_build_url_from_path_buf;
+use std::fs::{create_dir, write};
+use tempfile::TempDir;
+
+#[test]
+fn file_metadata_can_be_fetched() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test_stat_record");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    write(&target_file, "Foobar").expect("Could not write payload to file");
+
+    let metadata = stat_url(&url, &registry).expect("Could not stat url");
+    assert!(metadata.exists);
+    assert!(!metadata.is_container);
+    assert_eq!(Some(6), metadata.size);
+    assert!(metadata.modified.is_some());
+    assert_eq!(Some(UrlVersion::from_content(b"Foobar")), metadata.version);
+    #[cfg(unix)]
+    assert!(metadata.permissions.is_some());
+}
+
+#[test]
+fn file_container_metadata_can_be_fetched() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_dir = tmp_dir.path().join("test_stat_container");
+    let url = try_build_url_from_path_buf(&target_dir).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    create_dir(&target_dir).expect("Could not create directory");
+
+    let metadata = stat_url(&url, &registry).expect("Could not stat url");
+    assert!(metadata.exists);
+    assert!(metadata.is_container);
+    assert_eq!(None, metadata.version);
+}
+
+#[test]
+fn missing_file_does_not_exist() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test_missing_record");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    assert!(!url_exists(&url, &registry).expect("Could not stat url"));
+    assert_eq!(
+        UrlMetadata::missing(),
+        stat_url(&url, &registry).expect("Could not stat url")
+    );
+}
+
+#[test]
+fn http_metadata_can_be_fetched() {
+    let server = TestServer::start();
+    let registry = ProtocolHandlerRegistry::default();
+    server.put("/record.json", b"{}");
+
+    let metadata = stat_url(&server.url("/record.json"), &registry).expect("Could not stat url");
+    assert!(metadata.exists);
+    assert_eq!(
+        Some(UrlVersion::ETag("\"0\"".to_string())),
+        metadata.version
+    );
+
+    assert!(!url_exists(&server.url("/missing.json"), &registry).expect("Could not stat url"));
+}
+
+#[test]
+fn data_url_metadata_can_be_fetched() {
+    let url = Url::parse("data:,Foobar").expect("Could not parse url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let metadata = stat_url(&url, &registry).expect("Could not stat url");
+    assert!(metadata.exists);
+    assert_eq!(Some(6), metadata.size);
+}
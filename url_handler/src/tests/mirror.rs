@@ -0,0 +1,93 @@
+use super::http::TestServer;
+use super::*;
+use std::fs::write;
+use std::net::TcpListener;
+use tempfile::TempDir;
+
+fn build_unreachable_url() -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind port");
+    let address = listener.local_addr().expect("Could not get address");
+    Url::parse(&format!("http://{address}/app.toml")).expect("Could not build url")
+}
+
+#[test]
+fn mirrors_fall_back_in_order() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let cache = tmp_dir.path().join("app.toml");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let good_record = NestedStruct::build_struct_with_items();
+    let string = build_string_from_record_with_extension(&good_record, "toml", &format_handlers)
+        .expect("Could not serialize record");
+    write(&cache, &string).expect("Could not write cache");
+    let server = TestServer::start();
+    server.put("/secondary/app.toml", string.as_bytes());
+
+    let cache_url = try_build_url_from_path_buf(&cache).expect("Could not build url");
+    let urls = vec![
+        build_unreachable_url(),
+        server.url("/missing/app.toml"),
+        server.url("/secondary/app.toml"),
+        cache_url.clone(),
+    ];
+    let (candidate, source): (NestedStruct, Url) =
+        build_record_from_mirrors(&urls, &protocol_handlers, &format_handlers)
+            .expect("Could not fetch from mirrors");
+    assert_eq!(good_record, candidate);
+    assert_eq!(server.url("/secondary/app.toml"), source);
+
+    drop(server);
+    let (_, source): (NestedStruct, Url) =
+        build_record_from_mirrors(&urls, &protocol_handlers, &format_handlers)
+            .expect("Could not fetch from mirrors");
+    assert_eq!(cache_url, source);
+
+    let error =
+        build_record_from_mirrors::<NestedStruct>(&urls[..2], &protocol_handlers, &format_handlers)
+            .expect_err("Mirrors should have failed");
+    let mirror_error = error
+        .downcast_ref::<MirrorError>()
+        .expect("Error is not a MirrorError");
+    let failed: Vec<&Url> = mirror_error.errors().iter().map(|(url, _)| url).collect();
+    assert_eq!(urls[..2].iter().collect::<Vec<_>>(), failed);
+}
+
+#[test]
+fn mirror_groups_are_read_from_config() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let cache = tmp_dir.path().join("app.json");
+    write(&cache, r#"{"a": 1}"#).expect("Could not write cache");
+    let cache_url = try_build_url_from_path_buf(&cache).expect("Could not build url");
+    let config: ProtocolHandlerConfig = toml::from_str(&format!(
+        r#"
+        [http]
+        [mirrors]
+        settings = ["{}", "{cache_url}"]
+        empty = []
+        "#,
+        build_unreachable_url()
+    ))
+    .expect("Could not parse config");
+    let protocol_handlers = ProtocolHandlerRegistry::new(&config);
+    let format_handlers = FormatHandlerRegistry::default();
+
+    let (record, source): (HashMap<String, u32>, Url) =
+        build_record_from_mirror_group("settings", &protocol_handlers, &format_handlers)
+            .expect("Could not fetch from mirror group");
+    assert_eq!(HashMap::from([("a".to_string(), 1)]), record);
+    assert_eq!(cache_url, source);
+    assert!(build_record_from_mirror_group::<HashMap<String, u32>>(
+        "unknown",
+        &protocol_handlers,
+        &format_handlers
+    )
+    .is_err());
+
+    let error = build_record_from_mirror_group::<HashMap<String, u32>>(
+        "empty",
+        &protocol_handlers,
+        &format_handlers,
+    )
+    .expect_err("Empty group should fail");
+    assert_eq!("No mirrors configured for 'empty'", error.to_string());
+}
@@ -0,0 +1,124 @@
+use super::*;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn build_registry(base: &Path) -> ProtocolHandlerRegistry {
+    let base = try_build_url_from_path_buf(&base.to_path_buf()).expect("Could not build url");
+    let config: ProtocolHandlerConfig = toml::from_str(&format!(
+        r#"
+        [http]
+        [mounts]
+        prod = "{base}/"
+        "#
+    ))
+    .expect("Could not parse config");
+    ProtocolHandlerRegistry::new(&config)
+}
+
+#[test]
+fn mounted_urls_are_rewritten_before_dispatch() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let base = tmp_dir.path().join("configs");
+    create_dir_all(&base).expect("Could not create base");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = build_registry(&base);
+    let good_record = NestedStruct::build_struct_with_items();
+    let url = Url::parse("prod:/app.toml").expect("Could not build url");
+
+    push_record_to_url(&url, &good_record, &protocol_handlers, &format_handlers)
+        .expect("Could not push record");
+    assert!(base.join("app.toml").is_file());
+    let candidate: NestedStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+
+    let copy = Url::parse("prod:/copy.toml").expect("Could not build url");
+    copy_url(&url, &copy, &protocol_handlers).expect("Could not copy record");
+    assert_eq!(
+        read_to_string(base.join("app.toml")).expect("Could not read record"),
+        read_to_string(base.join("copy.toml")).expect("Could not read copy")
+    );
+
+    let unknown = Url::parse("staging:/app.toml").expect("Could not build url");
+    assert!(fetch_string_from_url(&unknown, &protocol_handlers).is_err());
+}
+
+#[test]
+fn listings_are_mapped_back_to_mounts() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let base = tmp_dir.path().join("configs");
+    create_dir_all(base.join("conf.d")).expect("Could not create base");
+    write(base.join("app.toml"), "Foo").expect("Could not write record");
+    write(base.join("conf.d/extra.toml"), "Bar").expect("Could not write record");
+    let registry = build_registry(&base);
+
+    let root = Url::parse("prod:/").expect("Could not build url");
+    let children: HashSet<String> = list_urls_in_url_container(&root, &registry)
+        .expect("Could not list mount")
+        .iter()
+        .map(Url::to_string)
+        .collect();
+    assert_eq!(
+        HashSet::from(["prod:/app.toml".to_string(), "prod:/conf.d".to_string()]),
+        children
+    );
+
+    let options = WalkOptions {
+        filter: WalkFilter::FilesOnly,
+        ..WalkOptions::default()
+    };
+    let files: Vec<String> = walk_url_container(&root, &options, &registry)
+        .expect("Could not walk mount")
+        .iter()
+        .map(Url::to_string)
+        .collect();
+    assert_eq!(vec!["prod:/app.toml", "prod:/conf.d/extra.toml"], files);
+
+    let record = fetch_string_from_url(
+        &Url::parse("prod:/conf.d/extra.toml").expect("Could not build url"),
+        &registry,
+    )
+    .expect("Could not fetch record");
+    assert_eq!(Some("Bar".to_string()), record);
+}
+
+#[test]
+fn plain_urls_below_a_mount_are_listed_unchanged() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let base = tmp_dir.path().join("configs");
+    create_dir_all(&base).expect("Could not create base");
+    write(base.join("app.toml"), "Foo").expect("Could not write record");
+    let registry = build_registry(&base);
+
+    let root = try_build_url_from_path_buf(&base).expect("Could not build url");
+    let children: Vec<Url> = list_urls_in_url_container(&root, &registry)
+        .expect("Could not list container")
+        .into_iter()
+        .collect();
+    let record = try_build_url_from_path_buf(&base.join("app.toml")).expect("Bad url");
+    assert_eq!(vec![record.clone()], children);
+    let files = walk_url_container(&root, &WalkOptions::default(), &registry)
+        .expect("Could not walk container");
+    assert_eq!(vec![record], files);
+
+    let target = tmp_dir.path().join("target");
+    let target = try_build_url_from_path_buf(&target).expect("Could not build url");
+    sync_url_containers(&root, &target, &SyncOptions::default(), &registry)
+        .expect("Could not sync containers");
+    let record = read_to_string(tmp_dir.path().join("target/app.toml")).expect("Could not read");
+    assert_eq!("Foo", record);
+}
+
+#[test]
+fn mounts_cannot_shadow_registered_schemes() {
+    for name in ["file", "https", "overlay", "zip+file"] {
+        let result: Result<ProtocolHandlerConfig, _> = toml::from_str(&format!(
+            r#"
+            [mounts]
+            "{name}" = "file:///srv/configs/"
+            "#
+        ));
+        assert!(result.is_err(), "Mount '{name}' should be rejected");
+    }
+}
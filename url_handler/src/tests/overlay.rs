@@ -0,0 +1,151 @@
+use super::http::TestServer;
+use super::*;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn build_registry(top: &Path, site: &Path, defaults: &Url) -> ProtocolHandlerRegistry {
+    let top = try_build_url_from_path_buf(&top.to_path_buf()).expect("Could not build url");
+    let site = try_build_url_from_path_buf(&site.to_path_buf()).expect("Could not build url");
+    let config: ProtocolHandlerConfig = toml::from_str(&format!(
+        r#"
+        [http]
+        [overlay.stacks.settings]
+        top = "{top}"
+        layers = ["{site}", "{defaults}"]
+        "#
+    ))
+    .expect("Could not parse config");
+    ProtocolHandlerRegistry::new(&config)
+}
+
+#[test]
+fn overlay_reads_from_first_layer_and_writes_to_top() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let (top, site) = (tmp_dir.path().join("user"), tmp_dir.path().join("site"));
+    create_dir_all(&top).expect("Could not create top layer");
+    create_dir_all(&site).expect("Could not create site layer");
+    let server = TestServer::start();
+    let registry = build_registry(&top, &site, &server.url("/defaults/"));
+
+    server.put("/defaults/app.toml", b"defaults");
+    let url = Url::parse("overlay://settings/app.toml").expect("Could not build url");
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("defaults".to_string()), record);
+
+    write(site.join("app.toml"), "site").expect("Could not write site layer");
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("site".to_string()), record);
+
+    update_bytes_at_url(
+        &url,
+        &mut |bytes| Ok([bytes, b"+user".to_vec()].concat()),
+        &registry,
+    )
+    .expect("Could not update record");
+    let top_record = read_to_string(top.join("app.toml")).expect("Could not read top layer");
+    assert_eq!("site+user", top_record);
+    let site_record = read_to_string(site.join("app.toml")).expect("Could not read site layer");
+    assert_eq!("site", site_record);
+
+    push_string_to_url(&url, "user", &registry).expect("Could not push record");
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("user".to_string()), record);
+
+    delete_string_from_url(&url, &registry).expect("Could not delete record");
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("site".to_string()), record);
+    assert!(delete_string_from_url(&url, &registry).is_err());
+    assert_eq!(Some(b"defaults".to_vec()), server.get("/defaults/app.toml"));
+}
+
+#[test]
+fn overlay_lists_union_of_layers() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let (top, site) = (tmp_dir.path().join("user"), tmp_dir.path().join("site"));
+    create_dir_all(top.join("conf.d")).expect("Could not create top layer");
+    create_dir_all(site.join("conf.d")).expect("Could not create site layer");
+    write(top.join("conf.d/a.toml"), "a").expect("Could not write top layer");
+    write(site.join("conf.d/a.toml"), "a").expect("Could not write site layer");
+    write(site.join("conf.d/b.toml"), "b").expect("Could not write site layer");
+    let server = TestServer::start();
+    let registry = build_registry(&top, &site, &server.url("/defaults/"));
+
+    let url = Url::parse("overlay://settings/conf.d").expect("Could not build url");
+    let children = list_urls_in_url_container(&url, &registry).expect("Could not list overlay");
+    let expected: HashSet<Url> = ["a.toml", "b.toml"]
+        .iter()
+        .map(|name| Url::parse(&format!("overlay://settings/conf.d/{name}")).expect("Bad url"))
+        .collect();
+    assert_eq!(expected, children);
+
+    let root = Url::parse("overlay://settings/").expect("Could not build url");
+    let metadata = stat_url(&root, &registry).expect("Could not stat overlay");
+    assert!(metadata.exists && metadata.is_container);
+    let missing = Url::parse("overlay://settings/missing/").expect("Could not build url");
+    assert!(list_urls_in_url_container(&missing, &registry).is_err());
+    let unknown = Url::parse("overlay://unknown/app.toml").expect("Could not build url");
+    assert!(fetch_string_from_url(&unknown, &registry).is_err());
+}
+
+#[test]
+fn overlay_skips_unreachable_layers() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let top = try_build_url_from_path_buf(&tmp_dir.path().join("user"))
+        .expect("Could not build url");
+    let site = try_build_url_from_path_buf(&tmp_dir.path().join("site"))
+        .expect("Could not build url");
+    create_dir_all(tmp_dir.path().join("site/conf.d")).expect("Could not create site layer");
+    write(tmp_dir.path().join("site/app.toml"), "site").expect("Could not write site layer");
+    let config: ProtocolHandlerConfig = toml::from_str(&format!(
+        r#"
+        [overlay.stacks.settings]
+        top = "{top}"
+        layers = ["http://127.0.0.1:1/defaults/", "{site}"]
+        "#
+    ))
+    .expect("Could not parse config");
+    let registry = ProtocolHandlerRegistry::new(&config);
+
+    let url = Url::parse("overlay://settings/app.toml").expect("Could not build url");
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("site".to_string()), record);
+
+    let missing = Url::parse("overlay://settings/missing.toml").expect("Could not build url");
+    assert!(fetch_string_from_url(&missing, &registry).is_err());
+
+    let root = Url::parse("overlay://settings/").expect("Could not build url");
+    let children = list_urls_in_url_container(&root, &registry).expect("Could not list overlay");
+    let expected: HashSet<Url> = ["app.toml", "conf.d/"]
+        .iter()
+        .map(|name| Url::parse(&format!("overlay://settings/{name}")).expect("Bad url"))
+        .collect();
+    assert_eq!(expected, children);
+}
+
+#[test]
+fn overlay_cycles_are_rejected_on_load() {
+    let result: Result<ProtocolHandlerConfig, _> = toml::from_str(
+        r#"
+        [overlay.stacks.a]
+        top = "file:///tmp/a/"
+        layers = ["overlay://b/"]
+        [overlay.stacks.b]
+        top = "file:///tmp/b/"
+        layers = ["overlay://a/"]
+        "#,
+    );
+    let error = result.expect_err("Cycle should be rejected").to_string();
+    assert!(error.contains("cycle"), "Unexpected error: {error}");
+
+    let result: Result<ProtocolHandlerConfig, _> = toml::from_str(
+        r#"
+        [overlay.stacks.a]
+        top = "file:///tmp/a/"
+        layers = ["overlay://b/"]
+        [overlay.stacks.b]
+        top = "file:///tmp/b/"
+        "#,
+    );
+    assert!(result.is_ok());
+}
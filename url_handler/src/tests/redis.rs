@@ -0,0 +1,372 @@
+use super::*;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Clone, Debug)]
+struct Entry {
+    value: Vec<u8>,
+    ttl: Option<u64>,
+    version: u64,
+}
+
+type Entries = Arc<Mutex<HashMap<(i64, Vec<u8>), Entry>>>;
+
+// Speaks just enough RESP2 for the redis handler.
+struct RedisTestServer {
+    address: SocketAddr,
+    entries: Entries,
+}
+
+impl RedisTestServer {
+    fn start(password: Option<&str>) -> RedisTestServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let address = listener.local_addr().expect("Could not get address");
+        let entries: Entries = Arc::default();
+        let versions = Arc::new(AtomicU64::new(1));
+
+        let thread_entries = entries.clone();
+        let password = password.map(String::from);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let entries = thread_entries.clone();
+                let versions = versions.clone();
+                let password = password.clone();
+                thread::spawn(move || {
+                    let _ = handle_connection(stream, &entries, &versions, password.as_deref());
+                });
+            }
+        });
+        RedisTestServer { address, entries }
+    }
+
+    fn url(&self, path: &str) -> Url {
+        Url::parse(&format!("redis://{}{path}", self.address)).expect("Could not build url")
+    }
+
+    fn get(&self, db: i64, key: &str) -> Option<Entry> {
+        let entries = self.entries.lock().expect("Could not lock entries");
+        entries.get(&(db, key.as_bytes().to_vec())).cloned()
+    }
+}
+
+fn read_command(reader: &mut impl BufRead) -> std::io::Result<Option<Vec<Vec<u8>>>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let count: usize = line.trim_end()[1..].parse().unwrap_or_default();
+    let mut arguments = Vec::with_capacity(count);
+    for _ in 0..count {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let length: usize = line.trim_end()[1..].parse().unwrap_or_default();
+        let mut argument = vec![0; length + 2];
+        reader.read_exact(&mut argument)?;
+        argument.truncate(length);
+        arguments.push(argument);
+    }
+    Ok(Some(arguments))
+}
+
+fn bulk(bytes: &[u8]) -> Vec<u8> {
+    let mut reply = format!("${}\r\n", bytes.len()).into_bytes();
+    reply.extend_from_slice(bytes);
+    reply.extend_from_slice(b"\r\n");
+    reply
+}
+
+fn array(items: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut reply = format!("*{}\r\n", items.len()).into_bytes();
+    items.into_iter().for_each(|item| reply.extend(item));
+    reply
+}
+
+fn integer(value: usize) -> Vec<u8> {
+    format!(":{value}\r\n").into_bytes()
+}
+
+fn unescape_prefix(pattern: &[u8]) -> Vec<u8> {
+    let pattern = pattern.strip_suffix(b"*").unwrap_or(pattern);
+    let mut prefix = Vec::new();
+    let mut escaped = false;
+    for byte in pattern {
+        match (escaped, byte) {
+            (false, b'\\') => escaped = true,
+            _ => {
+                prefix.push(*byte);
+                escaped = false;
+            }
+        }
+    }
+    prefix
+}
+
+fn execute(
+    arguments: &[Vec<u8>],
+    db: i64,
+    entries: &mut HashMap<(i64, Vec<u8>), Entry>,
+    versions: &AtomicU64,
+) -> Vec<u8> {
+    let name = String::from_utf8_lossy(&arguments[0]).to_uppercase();
+    let key = |index: usize| (db, arguments[index].clone());
+    match name.as_str() {
+        "GET" => match entries.get(&key(1)) {
+            Some(entry) => bulk(&entry.value),
+            None => b"$-1\r\n".to_vec(),
+        },
+        "SET" => {
+            let option = arguments
+                .get(3)
+                .map(|option| String::from_utf8_lossy(option).to_uppercase());
+            let previous_ttl = entries.get(&key(1)).and_then(|entry| entry.ttl);
+            let ttl = match option.as_deref() {
+                Some("PX") => String::from_utf8_lossy(&arguments[4]).parse().ok(),
+                Some("KEEPTTL") => previous_ttl,
+                _ => None,
+            };
+            let entry = Entry {
+                value: arguments[2].clone(),
+                ttl,
+                version: versions.fetch_add(1, Ordering::SeqCst),
+            };
+            entries.insert(key(1), entry);
+            b"+OK\r\n".to_vec()
+        }
+        "DEL" => integer(
+            (1..arguments.len())
+                .filter(|index| entries.remove(&key(*index)).is_some())
+                .count(),
+        ),
+        "EXISTS" => integer(entries.contains_key(&key(1)).into()),
+        "STRLEN" => integer(entries.get(&key(1)).map_or(0, |entry| entry.value.len())),
+        "SCAN" => {
+            let prefix = unescape_prefix(&arguments[3]);
+            let keys = entries
+                .keys()
+                .filter(|(key_db, key)| *key_db == db && key.starts_with(&prefix))
+                .map(|(_, key)| bulk(key))
+                .collect();
+            array(vec![bulk(b"0"), array(keys)])
+        }
+        _ => format!("-ERR unknown command '{name}'\r\n").into_bytes(),
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    entries: &Entries,
+    versions: &AtomicU64,
+    password: Option<&str>,
+) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut db = 0;
+    let mut is_authenticated = password.is_none();
+    let mut watched: Vec<(Vec<u8>, u64)> = Vec::new();
+    let mut queue: Option<Vec<Vec<Vec<u8>>>> = None;
+
+    while let Some(arguments) = read_command(&mut reader)? {
+        let name = String::from_utf8_lossy(&arguments[0]).to_uppercase();
+        let mut entries = entries.lock().expect("Could not lock entries");
+        let reply = match name.as_str() {
+            "AUTH" => {
+                is_authenticated = arguments.last().map(|argument| argument.as_slice())
+                    == password.map(str::as_bytes);
+                match is_authenticated {
+                    true => b"+OK\r\n".to_vec(),
+                    false => b"-WRONGPASS invalid password\r\n".to_vec(),
+                }
+            }
+            _ if !is_authenticated => b"-NOAUTH Authentication required.\r\n".to_vec(),
+            "CLIENT" => b"+OK\r\n".to_vec(),
+            "SELECT" => {
+                db = String::from_utf8_lossy(&arguments[1])
+                    .parse()
+                    .unwrap_or_default();
+                b"+OK\r\n".to_vec()
+            }
+            "WATCH" => {
+                for key in &arguments[1..] {
+                    let version = entries
+                        .get(&(db, key.clone()))
+                        .map_or(0, |entry| entry.version);
+                    watched.push((key.clone(), version));
+                }
+                b"+OK\r\n".to_vec()
+            }
+            "MULTI" => {
+                queue = Some(Vec::new());
+                b"+OK\r\n".to_vec()
+            }
+            "EXEC" => {
+                let is_conflict = watched.drain(..).any(|(key, version)| {
+                    entries.get(&(db, key)).map_or(0, |entry| entry.version) != version
+                });
+                let commands = queue.take().unwrap_or_default();
+                match is_conflict {
+                    true => b"*-1\r\n".to_vec(),
+                    false => array(
+                        commands
+                            .iter()
+                            .map(|command| execute(command, db, &mut entries, versions))
+                            .collect(),
+                    ),
+                }
+            }
+            _ => match &mut queue {
+                Some(queue) => {
+                    queue.push(arguments);
+                    b"+QUEUED\r\n".to_vec()
+                }
+                None => execute(&arguments, db, &mut entries, versions),
+            },
+        };
+        drop(entries);
+        writer.write_all(&reply)?;
+    }
+    Ok(())
+}
+
+fn build_registry(extra: &str) -> ProtocolHandlerRegistry {
+    let config: ProtocolHandlerConfig =
+        toml::from_str(&format!("[http]\n{extra}")).expect("Could not parse config");
+    ProtocolHandlerRegistry::new(&config)
+}
+
+#[test]
+fn records_can_be_stored_in_redis() {
+    let server = RedisTestServer::start(None);
+    let url = server.url("/2/services/app.toml");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let good_record = NestedStruct::build_struct_with_items();
+
+    push_record_to_url(&url, &good_record, &protocol_handlers, &format_handlers)
+        .expect("Could not push record");
+    let candidate: NestedStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+    assert!(server.get(2, "services/app.toml").is_some());
+    assert!(server.get(0, "services/app.toml").is_none());
+
+    let updated: NestedStruct = update_record_at_url(
+        &url,
+        |record: &mut NestedStruct| {
+            record.map.clear();
+            Ok(())
+        },
+        &protocol_handlers,
+        &format_handlers,
+    )
+    .expect("Could not update record");
+    assert!(updated.map.is_empty());
+
+    delete_string_from_url(&url, &protocol_handlers).expect("Could not delete record");
+    let record = fetch_string_from_url(&url, &protocol_handlers).expect("Could not fetch record");
+    assert_eq!(None, record);
+    assert!(delete_string_from_url(&url, &protocol_handlers).is_err());
+}
+
+#[test]
+fn redis_containers_are_key_prefixes() {
+    let server = RedisTestServer::start(None);
+    let registry = ProtocolHandlerRegistry::default();
+    for key in ["a.toml", "apps/b.toml", "apps/nested/c.toml", "apps*.toml"] {
+        let url = server.url(&format!("/0/{key}"));
+        push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    }
+
+    let children =
+        list_urls_in_url_container(&server.url("/0/"), &registry).expect("Could not list database");
+    let expected: HashSet<Url> = ["a.toml", "apps/", "apps*.toml"]
+        .iter()
+        .map(|child| server.url(&format!("/0/{child}")))
+        .collect();
+    assert_eq!(expected, children);
+
+    let apps = server.url("/0/apps/");
+    let children = list_urls_in_url_container(&apps, &registry).expect("Could not list prefix");
+    let expected: HashSet<Url> = ["b.toml", "nested/"]
+        .iter()
+        .map(|child| server.url(&format!("/0/apps/{child}")))
+        .collect();
+    assert_eq!(expected, children);
+
+    let metadata = stat_url(&apps, &registry).expect("Could not stat prefix");
+    assert!(metadata.exists && metadata.is_container);
+    let metadata = stat_url(&server.url("/0/a.toml"), &registry).expect("Could not stat key");
+    assert!(metadata.exists && !metadata.is_container);
+    assert_eq!(Some(3), metadata.size);
+
+    assert!(delete_url_container(&apps, false, &registry).is_err());
+    delete_url_container(&apps, true, &registry).expect("Could not delete prefix");
+    assert!(server.get(0, "apps/nested/c.toml").is_none());
+    assert!(server.get(0, "apps*.toml").is_some());
+    assert!(!url_exists(&apps, &registry).expect("Could not stat prefix"));
+}
+
+#[test]
+fn redis_push_applies_configured_ttl_and_credentials() {
+    let server = RedisTestServer::start(Some("hunter2"));
+    let registry = build_registry(
+        r#"
+        [redis]
+        ttl = "1h"
+        [redis.hosts."127.0.0.1"]
+        password = "hunter2"
+        "#,
+    );
+    let url = server.url("/0/settings.toml");
+    push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    assert_eq!(
+        Some(3_600_000),
+        server.get(0, "settings.toml").and_then(|e| e.ttl)
+    );
+
+    let url = server.url("/0/settings.toml?ttl=30s");
+    push_string_to_url(&url, "Bar", &registry).expect("Could not push string");
+    assert_eq!(
+        Some(30_000),
+        server.get(0, "settings.toml").and_then(|e| e.ttl)
+    );
+
+    let unauthenticated = ProtocolHandlerRegistry::default();
+    assert!(fetch_string_from_url(&url, &unauthenticated).is_err());
+}
+
+#[test]
+fn concurrent_redis_updates_are_retried() {
+    let server = RedisTestServer::start(None);
+    let url = server.url("/0/counter.json");
+    let registry = ProtocolHandlerRegistry::default();
+    push_string_to_url(&url, "0", &registry).expect("Could not push string");
+
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let url = url.clone();
+            let registry = registry.clone();
+            thread::spawn(move || {
+                for _ in 0..5 {
+                    update_bytes_at_url(
+                        &url,
+                        &mut |bytes| {
+                            let count: u64 = String::from_utf8(bytes)?.parse()?;
+                            Ok((count + 1).to_string().into_bytes())
+                        },
+                        &registry,
+                    )
+                    .expect("Could not update counter");
+                }
+            })
+        })
+        .collect();
+    workers
+        .into_iter()
+        .for_each(|worker| worker.join().expect("Worker panicked"));
+
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("20".to_string()), record);
+}
@@ -0,0 +1,85 @@
+use super::http::TestServer;
+use super::*;
+use std::net::TcpListener;
+use tempfile::TempDir;
+
+fn build_unreachable_url() -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind port");
+    let address = listener.local_addr().expect("Could not get address");
+    Url::parse(&format!("http://{address}/app.toml")).expect("Could not build url")
+}
+
+#[test]
+fn records_are_replicated_to_all_targets() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let good_record = NestedStruct::build_struct_with_items();
+    let server = TestServer::start();
+    let urls = vec![
+        try_build_url_from_path_buf(&tmp_dir.path().join("app.toml")).expect("Bad url"),
+        try_build_url_from_path_buf(&tmp_dir.path().join("app.json.gz")).expect("Bad url"),
+        server.url("/app.toml"),
+    ];
+
+    let report = push_record_to_urls(
+        &urls,
+        &good_record,
+        &ReplicationPolicy::All,
+        &protocol_handlers,
+        &format_handlers,
+    )
+    .expect("Could not replicate record");
+    assert_eq!(urls.iter().collect::<Vec<_>>(), report.succeeded());
+    for url in &urls {
+        let candidate: NestedStruct =
+            build_record_from_url(url, &protocol_handlers, &format_handlers)
+                .expect("Could not parse record");
+        assert_eq!(good_record, candidate);
+    }
+    assert!(push_record_to_urls(
+        &[],
+        &good_record,
+        &ReplicationPolicy::BestEffort,
+        &protocol_handlers,
+        &format_handlers,
+    )
+    .is_err());
+}
+
+#[test]
+fn replication_policies_decide_on_failures() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let good_record = NestedStruct::build_struct_with_items();
+    let file = try_build_url_from_path_buf(&tmp_dir.path().join("app.toml")).expect("Bad url");
+    let unreachable = build_unreachable_url();
+    let push = |urls: &[Url], policy: ReplicationPolicy| {
+        push_record_to_urls(
+            urls,
+            &good_record,
+            &policy,
+            &protocol_handlers,
+            &format_handlers,
+        )
+    };
+
+    let urls = vec![file.clone(), unreachable.clone()];
+    let error = push(&urls, ReplicationPolicy::All).expect_err("Replication should have failed");
+    let report = error
+        .downcast_ref::<ReplicationError>()
+        .expect("Error is not a ReplicationError")
+        .report();
+    assert_eq!(vec![&file], report.succeeded());
+    assert_eq!(unreachable, report.failed()[0].url);
+    assert!(push(&urls, ReplicationPolicy::Quorum).is_err());
+
+    let report = push(&urls, ReplicationPolicy::BestEffort).expect("Could not replicate record");
+    assert_eq!(1, report.failed().len());
+
+    let second = try_build_url_from_path_buf(&tmp_dir.path().join("b.json")).expect("Bad url");
+    let report = push(&[file, second, unreachable], ReplicationPolicy::Quorum)
+        .expect("Could not replicate record");
+    assert_eq!(2, report.succeeded().len());
+}
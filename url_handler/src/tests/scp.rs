@@ -0,0 +1,88 @@
+use super::*;
+use std::fs::{create_dir_all, read_to_string, write, File};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+// Runs the remote command locally, the destination and port are ignored.
+const SSH: &str = r#"#!/bin/sh
+[ "$1" = "-p" ] && shift 2
+shift
+exec sh -c "$1"
+"#;
+
+const SCP: &str = r#"#!/bin/sh
+strip() { printf '%s' "$1" | sed -e 's|^scp://[^/]*||'; }
+exec cp "$(strip "$1")" "$(strip "$2")"
+"#;
+
+// Puts `ssh` and `scp` stand-ins in front of the PATH so scp URLs work against localhost.
+pub fn install_fake_ssh() {
+    static BIN: OnceLock<TempDir> = OnceLock::new();
+    BIN.get_or_init(|| {
+        let bin = TempDir::new().expect("Could not create TempDir");
+        for (name, script) in [("ssh", SSH), ("scp", SCP)] {
+            let path = bin.path().join(name);
+            write(&path, script).expect("Could not write script");
+            let permissions = std::fs::Permissions::from_mode(0o755);
+            std::fs::set_permissions(&path, permissions).expect("Could not make script executable");
+        }
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let paths = std::iter::once(bin.path().to_path_buf()).chain(std::env::split_paths(&path));
+        std::env::set_var(
+            "PATH",
+            std::env::join_paths(paths).expect("Could not join PATH"),
+        );
+        bin
+    });
+}
+
+pub fn build_scp_url(path: &Path) -> Url {
+    try_build_url_from_path_buf_with_hostname(&path.to_path_buf(), "localhost")
+        .expect("Could not build url")
+}
+
+#[test]
+fn scp_records_can_be_deleted() {
+    install_fake_ssh();
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let record = tmp_dir.path().join("app.toml");
+    write(&record, "Foo").expect("Could not write record");
+    create_dir_all(tmp_dir.path().join("conf.d")).expect("Could not create directory");
+    let registry = ProtocolHandlerRegistry::default();
+
+    delete_string_from_url(&build_scp_url(&record), &registry).expect("Could not delete record");
+    assert!(!record.exists());
+    assert!(delete_string_from_url(&build_scp_url(&record), &registry).is_err());
+
+    let container = build_scp_url(&tmp_dir.path().join("conf.d"));
+    assert!(delete_string_from_url(&container, &registry).is_err());
+    assert!(tmp_dir.path().join("conf.d").is_dir());
+}
+
+#[test]
+fn stale_scp_locks_are_broken() {
+    install_fake_ssh();
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let record = tmp_dir.path().join("app.json");
+    write(&record, r#"{"id": 1}"#).expect("Could not write record");
+    let lock = tmp_dir.path().join("app.json.lock");
+    create_dir_all(&lock).expect("Could not create lock");
+    File::open(&lock)
+        .expect("Could not open lock")
+        .set_modified(SystemTime::now() - Duration::from_secs(3600))
+        .expect("Could not age lock");
+    let registry = ProtocolHandlerRegistry::default();
+
+    update_bytes_at_url(
+        &build_scp_url(&record),
+        &mut |bytes| Ok(String::from_utf8(bytes)?.replace('1', "2").into_bytes()),
+        &registry,
+    )
+    .expect("Could not update record");
+
+    assert_eq!(r#"{"id": 2}"#, read_to_string(&record).expect("Could not read"));
+    assert!(!lock.exists());
+}
@@ -0,0 +1,127 @@
+use super::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn build_sqlite_url(database: &Path, suffix: &str) -> Url {
+    Url::parse(&format!("sqlite://{}{suffix}", database.display()))
+        .expect("Could not build sqlite url")
+}
+
+#[test]
+fn records_can_be_stored_in_sqlite() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let database = tmp_dir.path().join("records.sqlite");
+    let url = build_sqlite_url(&database, "/configs/apps/app.toml");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let good_record = NestedStruct::build_struct_with_items();
+
+    push_record_to_url(&url, &good_record, &protocol_handlers, &format_handlers)
+        .expect("Could not push record");
+    let candidate: NestedStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+    assert!(database.is_file());
+
+    let updated: NestedStruct = update_record_at_url(
+        &url,
+        |record: &mut NestedStruct| {
+            record.map.clear();
+            Ok(())
+        },
+        &protocol_handlers,
+        &format_handlers,
+    )
+    .expect("Could not update record");
+    assert!(updated.map.is_empty());
+
+    delete_string_from_url(&url, &protocol_handlers).expect("Could not delete record");
+    let record = fetch_string_from_url(&url, &protocol_handlers).expect("Could not fetch record");
+    assert_eq!(None, record);
+    assert!(delete_string_from_url(&url, &protocol_handlers).is_err());
+}
+
+#[test]
+fn sqlite_containers_are_key_prefixes() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let database = tmp_dir.path().join("records.db");
+    let registry = ProtocolHandlerRegistry::default();
+    for key in [
+        "a.toml",
+        "apps/b.toml",
+        "apps/nested/c.toml",
+        "apps_other.toml",
+    ] {
+        let url = build_sqlite_url(&database, &format!("/configs/{key}"));
+        push_string_to_url(&url, "Foo", &registry).expect("Could not push string");
+    }
+
+    let table = build_sqlite_url(&database, "/configs/");
+    let children = list_urls_in_url_container(&table, &registry).expect("Could not list table");
+    let expected: HashSet<Url> = ["a.toml", "apps/", "apps_other.toml"]
+        .iter()
+        .map(|child| build_sqlite_url(&database, &format!("/configs/{child}")))
+        .collect();
+    assert_eq!(expected, children);
+
+    let apps = build_sqlite_url(&database, "/configs/apps/");
+    let children = list_urls_in_url_container(&apps, &registry).expect("Could not list prefix");
+    let expected: HashSet<Url> = ["b.toml", "nested/"]
+        .iter()
+        .map(|child| build_sqlite_url(&database, &format!("/configs/apps/{child}")))
+        .collect();
+    assert_eq!(expected, children);
+
+    let tables = list_urls_in_url_container(&build_sqlite_url(&database, "/"), &registry)
+        .expect("Could not list tables");
+    assert_eq!(HashSet::from([table.clone()]), tables);
+
+    let metadata = stat_url(&apps, &registry).expect("Could not stat prefix");
+    assert!(metadata.exists && metadata.is_container);
+    let metadata = stat_url(&build_sqlite_url(&database, "/configs/a.toml"), &registry)
+        .expect("Could not stat key");
+    assert!(metadata.exists && !metadata.is_container);
+    assert_eq!(Some(3), metadata.size);
+
+    assert!(delete_url_container(&apps, false, &registry).is_err());
+    delete_url_container(&apps, true, &registry).expect("Could not delete prefix");
+    let children = list_urls_in_url_container(&table, &registry).expect("Could not list table");
+    assert_eq!(2, children.len());
+    assert!(!url_exists(&apps, &registry).expect("Could not stat prefix"));
+}
+
+#[test]
+fn sqlite_rejects_invalid_table_names() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let database = tmp_dir.path().join("records.sqlite");
+    let registry = ProtocolHandlerRegistry::default();
+    let url = build_sqlite_url(&database, "/bad%22table/key.toml");
+
+    assert!(push_string_to_url(&url, "Foo", &registry).is_err());
+    assert!(
+        push_string_to_url(&build_sqlite_url(&database, "/configs"), "Foo", &registry).is_err()
+    );
+}
+
+#[test]
+fn sqlite_reads_do_not_create_databases_or_tables() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let database = tmp_dir.path().join("records.sqlite");
+    let registry = ProtocolHandlerRegistry::default();
+    let url = build_sqlite_url(&database, "/configs/app.toml");
+
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(None, record);
+    assert!(!url_exists(&url, &registry).expect("Could not stat url"));
+    assert!(!database.exists());
+
+    push_string_to_url(&build_sqlite_url(&database, "/other/app.toml"), "Foo", &registry)
+        .expect("Could not push string");
+    let record = fetch_string_from_url(&url, &registry).expect("Could not fetch record");
+    assert_eq!(None, record);
+    let configs = build_sqlite_url(&database, "/configs/");
+    assert!(!url_exists(&configs, &registry).expect("Could not stat table"));
+    let tables = list_urls_in_url_container(&build_sqlite_url(&database, "/"), &registry)
+        .expect("Could not list tables");
+    assert_eq!(HashSet::from([build_sqlite_url(&database, "/other/")]), tables);
+}
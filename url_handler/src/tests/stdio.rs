@@ -0,0 +1,50 @@
+use super::*;
+use std::env::current_dir;
+
+#[test]
+fn dash_is_built_into_stdio_url() {
+    let url = try_build_url_from_str("-").expect("Could not build url");
+    assert_eq!("stdio", url.scheme());
+
+    let registry = ProtocolHandlerRegistry::default();
+    assert!(registry.get_handler_for_protocol(url.scheme()).is_some());
+}
+
+#[test]
+fn urls_and_paths_can_be_built_from_str() {
+    let url = try_build_url_from_str("env:FOO").expect("Could not build url");
+    assert_eq!("env", url.scheme());
+
+    let url = try_build_url_from_str("test.toml").expect("Could not build url");
+    let expected = try_build_url_from_path_buf(
+        &current_dir()
+            .expect("Could not get working directory")
+            .join("test.toml"),
+    )
+    .expect("Could not build url");
+    assert_eq!(expected, url);
+}
+
+#[test]
+fn empty_string_can_be_pushed_to_stdout() {
+    let url = try_build_url_from_str("-").expect("Could not build url");
+
+    let handler = StdioProtocolHandler::default();
+    handler
+        .create_empty_string_on_url(&url)
+        .expect("Could not push record");
+    assert!(handler.delete_string_from_url(&url).is_err());
+}
+
+#[test]
+fn record_can_not_be_pushed_to_stdout_without_format() {
+    let url = try_build_url_from_str("-").expect("Could not build url");
+
+    let result = push_record_to_url(
+        &url,
+        &TestStruct::build_foo(),
+        &ProtocolHandlerRegistry::default(),
+        &FormatHandlerRegistry::default(),
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,233 @@
+use super::scp::{build_scp_url, install_fake_ssh};
+use super::*;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn build_url(path: &Path) -> Url {
+    try_build_url_from_path_buf(&path.to_path_buf()).expect("Could not build url")
+}
+
+fn count_actions(summary: &SyncSummary) -> (usize, usize, usize, usize) {
+    let mut counts = (0, 0, 0, 0);
+    for action in &summary.actions {
+        match action {
+            SyncAction::CreateContainer { .. } => counts.0 += 1,
+            SyncAction::Create { .. } => counts.1 += 1,
+            SyncAction::Update { .. } => counts.2 += 1,
+            SyncAction::Delete { .. } => counts.3 += 1,
+        }
+    }
+    counts
+}
+
+#[test]
+fn file_containers_can_be_synced() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let source = tmp_dir.path().join("source");
+    let target = tmp_dir.path().join("target");
+    create_dir_all(source.join("nested")).expect("Could not create directory");
+    write(source.join("a.toml"), "Foo").expect("Could not write file");
+    write(source.join("nested/b.toml"), "Bar").expect("Could not write file");
+    let registry = ProtocolHandlerRegistry::default();
+    let options = SyncOptions::default();
+
+    let summary = sync_url_containers(
+        &build_url(&source),
+        &build_url(&target),
+        &options,
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert_eq!((2, 2, 0, 0), count_actions(&summary));
+    assert_eq!(
+        "Bar",
+        read_to_string(target.join("nested/b.toml")).expect("Could not read")
+    );
+
+    let summary = sync_url_containers(
+        &build_url(&source),
+        &build_url(&target),
+        &options,
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert_eq!((0, 0, 0, 0), count_actions(&summary));
+    assert_eq!(3, summary.unchanged.len());
+
+    write(source.join("a.toml"), "Foobar").expect("Could not write file");
+    let summary = sync_url_containers(
+        &build_url(&source),
+        &build_url(&target),
+        &options,
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert_eq!((0, 0, 1, 0), count_actions(&summary));
+    assert_eq!(
+        "Foobar",
+        read_to_string(target.join("a.toml")).expect("Could not read")
+    );
+}
+
+#[test]
+fn extraneous_entries_are_deleted_unless_dry_run() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let source = tmp_dir.path().join("source");
+    let target = tmp_dir.path().join("target");
+    create_dir_all(&source).expect("Could not create directory");
+    create_dir_all(&target).expect("Could not create directory");
+    write(source.join("a.toml"), "Foo").expect("Could not write file");
+    write(target.join("stale.toml"), "Bar").expect("Could not write file");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let options = SyncOptions {
+        delete_extraneous: true,
+        dry_run: true,
+        ..SyncOptions::default()
+    };
+    let summary = sync_url_containers(
+        &build_url(&source),
+        &build_url(&target),
+        &options,
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert!(summary.dry_run);
+    assert_eq!((0, 1, 0, 1), count_actions(&summary));
+    assert!(target.join("stale.toml").exists());
+    assert!(!target.join("a.toml").exists());
+
+    let options = SyncOptions {
+        delete_extraneous: true,
+        ..SyncOptions::default()
+    };
+    sync_url_containers(
+        &build_url(&source),
+        &build_url(&target),
+        &options,
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert!(!target.join("stale.toml").exists());
+    assert!(target.join("a.toml").exists());
+}
+
+#[test]
+fn hash_comparison_detects_changes_metadata_misses() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let source = tmp_dir.path().join("source");
+    let target = tmp_dir.path().join("target");
+    create_dir_all(&source).expect("Could not create directory");
+    create_dir_all(&target).expect("Could not create directory");
+    write(source.join("a.toml"), "Foo").expect("Could not write file");
+    write(target.join("a.toml"), "Bar").expect("Could not write file");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let summary = sync_url_containers(
+        &build_url(&source),
+        &build_url(&target),
+        &SyncOptions::default(),
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert_eq!((0, 0, 0, 0), count_actions(&summary));
+
+    let options = SyncOptions {
+        compare: SyncCompare::Hash,
+        ..SyncOptions::default()
+    };
+    let summary = sync_url_containers(
+        &build_url(&source),
+        &build_url(&target),
+        &options,
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert_eq!((0, 0, 1, 0), count_actions(&summary));
+    assert_eq!(
+        "Foo",
+        read_to_string(target.join("a.toml")).expect("Could not read")
+    );
+}
+
+#[test]
+fn file_container_can_be_synced_to_scp() {
+    install_fake_ssh();
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let source = tmp_dir.path().join("source");
+    let target = tmp_dir.path().join("remote/target");
+    create_dir_all(source.join("nested")).expect("Could not create directory");
+    write(source.join("a.toml"), "Foo").expect("Could not write file");
+    write(source.join("nested/b.toml"), "Bar").expect("Could not write file");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let summary = sync_url_containers(
+        &build_url(&source),
+        &build_scp_url(&target),
+        &SyncOptions::default(),
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert_eq!((2, 2, 0, 0), count_actions(&summary));
+    assert_eq!(
+        "Bar",
+        read_to_string(target.join("nested/b.toml")).expect("Could not read")
+    );
+
+    write(target.join("stale.toml"), "Baz").expect("Could not write file");
+    let options = SyncOptions {
+        delete_extraneous: true,
+        ..SyncOptions::default()
+    };
+    let summary = sync_url_containers(
+        &build_url(&source),
+        &build_scp_url(&target),
+        &options,
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert_eq!((0, 0, 0, 1), count_actions(&summary));
+    assert!(!target.join("stale.toml").exists());
+}
+
+#[test]
+fn type_conflicts_are_reported_and_left_alone() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let source = tmp_dir.path().join("source");
+    let target = tmp_dir.path().join("target");
+    create_dir_all(source.join("conf.d")).expect("Could not create directory");
+    write(source.join("conf.d/a.toml"), "Foo").expect("Could not write file");
+    write(source.join("app.toml"), "Bar").expect("Could not write file");
+    create_dir_all(target.join("app.toml")).expect("Could not create directory");
+    write(target.join("app.toml/keep.toml"), "Baz").expect("Could not write file");
+    write(target.join("conf.d"), "Qux").expect("Could not write file");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let options = SyncOptions {
+        delete_extraneous: true,
+        ..SyncOptions::default()
+    };
+    let summary = sync_url_containers(
+        &build_url(&source),
+        &build_url(&target),
+        &options,
+        &registry,
+    )
+    .expect("Could not sync containers");
+    assert_eq!((0, 0, 0, 0), count_actions(&summary));
+    assert!(summary.unchanged.is_empty());
+    let conflicts: HashSet<Url> = summary.conflicts.into_iter().collect();
+    assert_eq!(
+        HashSet::from([
+            build_url(&target.join("app.toml")),
+            build_url(&target.join("conf.d"))
+        ]),
+        conflicts
+    );
+    assert!(target.join("app.toml/keep.toml").exists());
+    assert_eq!(
+        "Qux",
+        read_to_string(target.join("conf.d")).expect("Could not read")
+    );
+}
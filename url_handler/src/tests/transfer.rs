@@ -0,0 +1,191 @@
+use super::http::TestServer;
+use super::scp::{build_scp_url, install_fake_ssh};
+use super::*;
+use std::fs::{create_dir_all, read_to_string, write};
+use tempfile::TempDir;
+
+#[test]
+fn file_can_be_copied_and_moved() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let source = try_build_url_from_path_buf(&tmp_dir.path().join("source.toml"))
+        .expect("Could not build url");
+    let copy = try_build_url_from_path_buf(&tmp_dir.path().join("copy.toml"))
+        .expect("Could not build url");
+    let moved = try_build_url_from_path_buf(&tmp_dir.path().join("moved.toml"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    write(tmp_dir.path().join("source.toml"), "Foobar").expect("Could not write file");
+
+    copy_url(&source, &copy, &registry).expect("Could not copy url");
+    move_url(&source, &moved, &registry).expect("Could not move url");
+
+    assert!(!url_exists(&source, &registry).expect("Could not stat url"));
+    let record = fetch_string_from_url(&copy, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foobar".to_string()), record);
+    let record = fetch_string_from_url(&moved, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foobar".to_string()), record);
+}
+
+#[test]
+fn file_container_is_copied_recursively() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    create_dir_all(tmp_dir.path().join("source/nested")).expect("Could not create directory");
+    write(tmp_dir.path().join("source/a.toml"), "Foo").expect("Could not write file");
+    write(tmp_dir.path().join("source/nested/b.toml"), "Bar").expect("Could not write file");
+    let source =
+        try_build_url_from_path_buf(&tmp_dir.path().join("source")).expect("Could not build url");
+    let target =
+        try_build_url_from_path_buf(&tmp_dir.path().join("target")).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    copy_url(&source, &target, &registry).expect("Could not copy container");
+
+    let record = read_to_string(tmp_dir.path().join("target/a.toml")).expect("Could not read");
+    assert_eq!("Foo", record);
+    let record =
+        read_to_string(tmp_dir.path().join("target/nested/b.toml")).expect("Could not read");
+    assert_eq!("Bar", record);
+}
+
+#[test]
+fn container_can_be_copied_across_protocols() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    create_dir_all(tmp_dir.path().join("source/nested")).expect("Could not create directory");
+    write(tmp_dir.path().join("source/nested/b.toml"), "Bar").expect("Could not write file");
+    let source =
+        try_build_url_from_path_buf(&tmp_dir.path().join("source")).expect("Could not build url");
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.zip"))
+        .expect("Could not build url");
+    let target = Url::parse(&format!("zip+{archive}!/")).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    copy_url(&source, &target, &registry).expect("Could not copy container");
+
+    let member = Url::parse(&format!("zip+{archive}!/nested/b.toml")).expect("Bad url");
+    let record = fetch_string_from_url(&member, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Bar".to_string()), record);
+}
+
+#[test]
+fn http_uses_server_side_copy_and_move() {
+    let server = TestServer::start();
+    let registry = ProtocolHandlerRegistry::default();
+    server.put("/source.json", b"{}");
+
+    copy_url(
+        &server.url("/source.json"),
+        &server.url("/copy.json"),
+        &registry,
+    )
+    .expect("Could not copy url");
+    move_url(
+        &server.url("/source.json"),
+        &server.url("/moved.json"),
+        &registry,
+    )
+    .expect("Could not move url");
+
+    assert_eq!(None, server.get("/source.json"));
+    assert_eq!(Some(b"{}".to_vec()), server.get("/copy.json"));
+    assert_eq!(Some(b"{}".to_vec()), server.get("/moved.json"));
+}
+
+#[test]
+fn http_record_can_be_moved_to_file() {
+    let server = TestServer::start();
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target = try_build_url_from_path_buf(&tmp_dir.path().join("record.json"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    server.put("/record.json", b"{}");
+
+    move_url(&server.url("/record.json"), &target, &registry).expect("Could not move url");
+
+    assert_eq!(None, server.get("/record.json"));
+    let record = read_to_string(tmp_dir.path().join("record.json")).expect("Could not read");
+    assert_eq!("{}", record);
+}
+
+#[test]
+fn container_is_not_copied_into_itself() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    create_dir_all(tmp_dir.path().join("source")).expect("Could not create directory");
+    write(tmp_dir.path().join("source/a.toml"), "Foo").expect("Could not write file");
+    let source =
+        try_build_url_from_path_buf(&tmp_dir.path().join("source")).expect("Could not build url");
+    let target = try_build_url_from_path_buf(&tmp_dir.path().join("source/nested/copy"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    assert!(copy_url(&source, &target, &registry).is_err());
+    assert!(move_url(&source, &target, &registry).is_err());
+    assert!(!tmp_dir.path().join("source/nested").exists());
+
+    let sibling = try_build_url_from_path_buf(&tmp_dir.path().join("source-copy"))
+        .expect("Could not build url");
+    copy_url(&source, &sibling, &registry).expect("Could not copy container");
+}
+
+#[test]
+fn scp_record_can_be_moved_to_file() {
+    install_fake_ssh();
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    write(tmp_dir.path().join("remote.toml"), "Foo").expect("Could not write file");
+    let source = build_scp_url(&tmp_dir.path().join("remote.toml"));
+    let target = try_build_url_from_path_buf(&tmp_dir.path().join("local.toml"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    move_url(&source, &target, &registry).expect("Could not move url");
+
+    assert!(!tmp_dir.path().join("remote.toml").exists());
+    let record = read_to_string(tmp_dir.path().join("local.toml")).expect("Could not read");
+    assert_eq!("Foo", record);
+}
+
+#[test]
+fn container_can_be_copied_into_missing_remote_containers() {
+    install_fake_ssh();
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    create_dir_all(tmp_dir.path().join("source/nested")).expect("Could not create directory");
+    write(tmp_dir.path().join("source/nested/b.toml"), "Bar").expect("Could not write file");
+    let source =
+        try_build_url_from_path_buf(&tmp_dir.path().join("source")).expect("Could not build url");
+    let server = TestServer::start();
+    let registry = ProtocolHandlerRegistry::default();
+
+    let remote = tmp_dir.path().join("remote/target");
+    copy_url(&source, &build_scp_url(&remote), &registry).expect("Could not copy to scp");
+    let record = read_to_string(remote.join("nested/b.toml")).expect("Could not read");
+    assert_eq!("Bar", record);
+
+    copy_url(&source, &server.url("/target"), &registry).expect("Could not copy to http");
+    assert_eq!(Some(b"Bar".to_vec()), server.get("/target/nested/b.toml"));
+}
+
+#[test]
+fn scp_containers_are_transferred_on_the_remote() {
+    install_fake_ssh();
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    create_dir_all(tmp_dir.path().join("source")).expect("Could not create directory");
+    create_dir_all(tmp_dir.path().join("copy")).expect("Could not create directory");
+    create_dir_all(tmp_dir.path().join("moved")).expect("Could not create directory");
+    write(tmp_dir.path().join("source/a.toml"), "Foo").expect("Could not write file");
+    let source = build_scp_url(&tmp_dir.path().join("source"));
+    let registry = ProtocolHandlerRegistry::default();
+
+    copy_url(&source, &build_scp_url(&tmp_dir.path().join("copy")), &registry)
+        .expect("Could not copy container");
+    let record = read_to_string(tmp_dir.path().join("copy/a.toml")).expect("Could not read");
+    assert_eq!("Foo", record);
+    assert!(!tmp_dir.path().join("copy/source").exists());
+
+    move_url(&source, &build_scp_url(&tmp_dir.path().join("moved")), &registry)
+        .expect("Could not move container");
+    let record = read_to_string(tmp_dir.path().join("moved/a.toml")).expect("Could not read");
+    assert_eq!("Foo", record);
+    assert!(!tmp_dir.path().join("source").exists());
+
+    let record = build_scp_url(&tmp_dir.path().join("moved/a.toml"));
+    assert!(move_url(&record, &build_scp_url(&tmp_dir.path().join("copy")), &registry).is_err());
+}
@@ -0,0 +1,143 @@
+use super::scp::{build_scp_url, install_fake_ssh};
+use super::*;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn build_registry(root: &Path) -> ProtocolHandlerRegistry {
+    let root = try_build_url_from_path_buf(&root.to_path_buf()).expect("Could not build url");
+    build_registry_for_root(&root)
+}
+
+fn build_registry_for_root(root: &Url) -> ProtocolHandlerRegistry {
+    let config: ProtocolHandlerConfig = toml::from_str(&format!(
+        r#"
+        [http]
+        [trash]
+        enabled = true
+        roots = ["{root}"]
+        retention = "30days"
+        "#
+    ))
+    .expect("Could not parse config");
+    ProtocolHandlerRegistry::new(&config)
+}
+
+#[test]
+fn deleted_record_is_moved_to_trash_and_restored() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    create_dir_all(tmp_dir.path().join("conf")).expect("Could not create directory");
+    let target_file = tmp_dir.path().join("conf/app.toml");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let registry = build_registry(tmp_dir.path());
+    write(&target_file, "Foobar").expect("Could not write file");
+
+    delete_string_from_url(&url, &registry).expect("Could not delete record");
+    assert!(!target_file.exists());
+
+    let trash = get_trash_container(&url, &registry).expect("Could not find trash");
+    assert_eq!(
+        tmp_dir.path().join(".trash"),
+        trash.to_file_path().expect("Could not build path")
+    );
+    let entries = list_trash(&trash, &registry).expect("Could not list trash");
+    assert_eq!(1, entries.len());
+    assert_eq!(url, entries[0].original);
+
+    let restored = restore_from_trash(&entries[0].url, &registry).expect("Could not restore");
+    assert_eq!(url, restored);
+    assert_eq!(
+        "Foobar",
+        read_to_string(&target_file).expect("Could not read file")
+    );
+    assert!(list_trash(&trash, &registry)
+        .expect("Could not list trash")
+        .is_empty());
+}
+
+#[test]
+fn deleted_container_is_moved_to_trash() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let container = tmp_dir.path().join("conf");
+    create_dir_all(&container).expect("Could not create directory");
+    write(container.join("app.toml"), "Foobar").expect("Could not write file");
+    let url = try_build_url_from_path_buf(&container).expect("Could not build url");
+    let registry = build_registry(tmp_dir.path());
+
+    delete_url_container(&url, true, &registry).expect("Could not delete container");
+    assert!(!container.exists());
+
+    let trash = get_trash_container(&url, &registry).expect("Could not find trash");
+    let entries = list_trash(&trash, &registry).expect("Could not list trash");
+    let path = entries[0].url.to_file_path().expect("Could not build path");
+    assert_eq!(
+        "Foobar",
+        read_to_string(path.join("app.toml")).expect("Could not read file")
+    );
+}
+
+#[test]
+fn trash_is_purged_after_retention() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("app.toml");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let registry = build_registry(tmp_dir.path());
+    write(&target_file, "Foobar").expect("Could not write file");
+    delete_string_from_url(&url, &registry).expect("Could not delete record");
+    let trash = get_trash_container(&url, &registry).expect("Could not find trash");
+
+    let purged = purge_trash(&trash, None, &registry).expect("Could not purge trash");
+    assert!(purged.is_empty());
+
+    let purged = purge_trash(&trash, Some(Duration::ZERO), &registry).expect("Could not purge");
+    assert_eq!(1, purged.len());
+    assert!(!url_exists(&purged[0].url, &registry).expect("Could not stat url"));
+    assert!(list_trash(&trash, &registry)
+        .expect("Could not list trash")
+        .is_empty());
+}
+
+#[test]
+fn trashing_keeps_the_delete_checks() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let container = tmp_dir.path().join("conf");
+    create_dir_all(&container).expect("Could not create directory");
+    write(container.join("app.toml"), "Foobar").expect("Could not write file");
+    let url = try_build_url_from_path_buf(&container).expect("Could not build url");
+    let registry = build_registry(tmp_dir.path());
+
+    assert!(delete_url_container(&url, false, &registry).is_err());
+    assert!(delete_string_from_url(&url, &registry).is_err());
+    assert!(container.join("app.toml").exists());
+    assert!(!tmp_dir.path().join(".trash").exists());
+}
+
+#[test]
+fn failed_delete_leaves_no_trash_behind() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url = try_build_url_from_path_buf(&tmp_dir.path().join("missing.toml"))
+        .expect("Could not build url");
+    let registry = build_registry(tmp_dir.path());
+
+    assert!(delete_string_from_url(&url, &registry).is_err());
+    assert!(!tmp_dir.path().join(".trash").exists());
+}
+
+#[test]
+fn scp_container_is_moved_to_trash() {
+    install_fake_ssh();
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let container = tmp_dir.path().join("conf");
+    create_dir_all(&container).expect("Could not create directory");
+    write(container.join("app.toml"), "Foobar").expect("Could not write file");
+    let registry = build_registry_for_root(&build_scp_url(tmp_dir.path()));
+    let url = build_scp_url(&container);
+
+    assert!(delete_url_container(&url, false, &registry).is_err());
+    delete_url_container(&url, true, &registry).expect("Could not delete container");
+    assert!(!container.exists());
+
+    let trash = get_trash_container(&url, &registry).expect("Could not find trash");
+    assert_eq!(1, list_trash(&trash, &registry).expect("Could not list trash").len());
+}
@@ -0,0 +1,66 @@
+use super::http::TestServer;
+use super::*;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn build_registry(socket: &Path) -> ProtocolHandlerRegistry {
+    let config: ProtocolHandlerConfig = toml::from_str(&format!(
+        r#"
+        [http.hosts."{}"]
+        bearer = "secret"
+        "#,
+        socket.display()
+    ))
+    .expect("Could not parse config");
+    ProtocolHandlerRegistry::new(&config)
+}
+
+#[test]
+fn url_can_be_built_from_unix_socket() {
+    let url = try_build_url_from_unix_socket(&PathBuf::from("/run/app.sock"), "/v1/app.toml")
+        .expect("Could not build url");
+
+    assert_eq!("http+unix://%2Frun%2Fapp.sock/v1/app.toml", url.as_str());
+}
+
+#[test]
+fn records_can_be_stored_over_unix_socket() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let socket = tmp_dir.path().join("app.sock");
+    let server = TestServer::start_unix(&socket, Some("secret"));
+    let url = server.url("/configs/app.toml");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = build_registry(&socket);
+    let good_record = NestedStruct::build_struct_with_items();
+
+    push_record_to_url(&url, &good_record, &protocol_handlers, &format_handlers)
+        .expect("Could not push record");
+    let candidate: NestedStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+    assert!(server.get("/configs/app.toml").is_some());
+
+    let copy = server.url("/configs/copy.toml");
+    copy_url(&url, &copy, &protocol_handlers).expect("Could not copy record");
+    assert_eq!(
+        server.get("/configs/app.toml"),
+        server.get("/configs/copy.toml")
+    );
+
+    let unauthenticated = ProtocolHandlerRegistry::default();
+    assert!(fetch_string_from_url(&url, &unauthenticated).is_err());
+}
+
+#[test]
+fn config_without_http_section_can_be_parsed() {
+    let config: ProtocolHandlerConfig = toml::from_str(
+        r#"
+        [file]
+        sync_parent_directory = true
+        "#,
+    )
+    .expect("Could not parse config");
+    let registry = ProtocolHandlerRegistry::new(&config);
+
+    assert!(registry.get_handler_for_protocol("http+unix").is_some());
+}
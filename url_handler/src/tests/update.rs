@@ -0,0 +1,157 @@
+use super::http::TestServer;
+use super::*;
+use crate::try_build_url_from_path_buf;
+use std::thread;
+use tempfile::TempDir;
+
+fn increment_concurrently(url: &Url, threads: u32, increments: u32) {
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let url = url.clone();
+            thread::spawn(move || {
+                let protocol_handlers = ProtocolHandlerRegistry::default();
+                let format_handlers = FormatHandlerRegistry::default();
+                for _ in 0..increments {
+                    update_record_at_url(
+                        &url,
+                        |record: &mut TestStruct| {
+                            record.id += 1;
+                            Ok(())
+                        },
+                        &protocol_handlers,
+                        &format_handlers,
+                    )
+                    .expect("Could not update record");
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("Update thread panicked");
+    }
+}
+
+#[test]
+fn record_can_be_updated_in_file() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url = try_build_url_from_path_buf(&tmp_dir.path().join("test.toml"))
+        .expect("Could not build url");
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let format_handlers = FormatHandlerRegistry::default();
+    push_record_to_url(
+        &url,
+        &TestStruct::build_foo(),
+        &protocol_handlers,
+        &format_handlers,
+    )
+    .expect("Could not push record");
+
+    let updated: TestStruct = update_record_at_url(
+        &url,
+        |record: &mut TestStruct| {
+            record.name = String::from("Bar");
+            Ok(())
+        },
+        &protocol_handlers,
+        &format_handlers,
+    )
+    .expect("Could not update record");
+
+    let candidate: TestStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+    assert_eq!("Bar", candidate.name);
+    assert_eq!(updated, candidate);
+
+    let entries: Vec<_> = tmp_dir
+        .path()
+        .read_dir()
+        .expect("Could not read dir")
+        .collect();
+    assert_eq!(1, entries.len());
+}
+
+#[test]
+fn failing_update_leaves_record_untouched() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url = try_build_url_from_path_buf(&tmp_dir.path().join("test.json"))
+        .expect("Could not build url");
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let format_handlers = FormatHandlerRegistry::default();
+    let good_record = TestStruct::build_foo();
+    push_record_to_url(&url, &good_record, &protocol_handlers, &format_handlers)
+        .expect("Could not push record");
+
+    let result = update_record_at_url(
+        &url,
+        |record: &mut TestStruct| {
+            record.name = String::from("Bar");
+            anyhow::bail!("Abort update")
+        },
+        &protocol_handlers,
+        &format_handlers,
+    );
+    assert!(result.is_err());
+
+    let candidate: TestStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+    assert_eq!(good_record, candidate);
+}
+
+#[test]
+fn concurrent_file_updates_are_serialized() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url = try_build_url_from_path_buf(&tmp_dir.path().join("test.toml.gz"))
+        .expect("Could not build url");
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let format_handlers = FormatHandlerRegistry::default();
+    let mut good_record = TestStruct::build_foo();
+    good_record.id = 0;
+    push_record_to_url(&url, &good_record, &protocol_handlers, &format_handlers)
+        .expect("Could not push record");
+
+    increment_concurrently(&url, 4, 10);
+
+    let candidate: TestStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+    assert_eq!(40, candidate.id);
+}
+
+#[test]
+fn concurrent_http_updates_are_retried() {
+    let server = TestServer::start();
+    let url = server.url("/test.json");
+    let mut good_record = TestStruct::build_foo();
+    good_record.id = 0;
+    server.put(
+        "/test.json",
+        serde_json::to_string(&good_record)
+            .expect("Could not serialize record")
+            .as_bytes(),
+    );
+
+    increment_concurrently(&url, 3, 5);
+
+    let bytes = server.get("/test.json").expect("Record is missing");
+    let candidate: TestStruct = serde_json::from_slice(&bytes).expect("Could not parse record");
+    assert_eq!(15, candidate.id);
+}
+
+#[test]
+fn concurrent_http_updates_without_etags_are_retried() {
+    let server = TestServer::start_without_etags();
+    let url = server.url("/test.json");
+    let mut good_record = TestStruct::build_foo();
+    good_record.id = 0;
+    server.put(
+        "/test.json",
+        serde_json::to_string(&good_record)
+            .expect("Could not serialize record")
+            .as_bytes(),
+    );
+
+    increment_concurrently(&url, 3, 5);
+
+    let bytes = server.get("/test.json").expect("Record is missing");
+    let candidate: TestStruct = serde_json::from_slice(&bytes).expect("Could not parse record");
+    assert_eq!(15, candidate.id);
+}
@@ -172,3 +172,20 @@ fn nested_struct_with_items_can_be_stored_in_toml() {
     let bad_record = NestedStruct::build_struct_without_items();
     assert_ne!(bad_record, candidate);
 }
+
+#[test]
+fn toml_record_with_unknown_extension_can_be_read() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("app.conf");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let format_handlers = FormatHandlerRegistry::default();
+    let protocol_handlers = ProtocolHandlerRegistry::default();
+    let good_record = TestStruct::build_foo();
+    let string = toml::to_string(&good_record).expect("Could not serialize record");
+    std::fs::write(&target_file, string).expect("Could not write record");
+
+    let candidate: TestStruct = build_record_from_url(&url, &protocol_handlers, &format_handlers)
+        .expect("Could not parse record");
+
+    assert_eq!(good_record, candidate);
+}
@@ -0,0 +1,112 @@
+use super::http::TestServer;
+use super::*;
+use crate::try_build_url_from_path_buf;
+use std::fs::write;
+use tempfile::TempDir;
+
+fn assert_conflict(result: Result<()>) {
+    let error = result.expect_err("Push should have failed");
+    assert!(error.is::<ConflictError>(), "Unexpected error: {error}");
+}
+
+#[test]
+fn file_can_be_pushed_with_matching_version() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test_versioned_record");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    write(&target_file, "Foo").expect("Could not write payload to file");
+
+    let (record, version) =
+        fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foo".to_string()), record);
+    assert!(version.is_some());
+
+    push_string_to_url_if_version(&url, "Foobar", version.as_ref(), &registry)
+        .expect("Could not push record");
+
+    let (record, new_version) =
+        fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foobar".to_string()), record);
+    assert_ne!(version, new_version);
+}
+
+#[test]
+fn file_push_with_stale_version_is_a_conflict() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let target_file = tmp_dir.path().join("test_versioned_record");
+    let url = try_build_url_from_path_buf(&target_file).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    write(&target_file, "Foo").expect("Could not write payload to file");
+
+    let (_, version) = fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    write(&target_file, "Bar").expect("Could not write payload to file");
+
+    assert_conflict(push_string_to_url_if_version(
+        &url,
+        "Foobar",
+        version.as_ref(),
+        &registry,
+    ));
+    let (record, _) = fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Bar".to_string()), record);
+}
+
+#[test]
+fn file_can_only_be_created_once_without_version() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let url = try_build_url_from_path_buf(&tmp_dir.path().join("test_versioned_record"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let (record, version) =
+        fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    assert_eq!(None, record);
+    assert_eq!(None, version);
+
+    push_string_to_url_if_version(&url, "Foo", None, &registry).expect("Could not push record");
+    assert_conflict(push_string_to_url_if_version(&url, "Bar", None, &registry));
+}
+
+#[test]
+fn http_can_be_pushed_with_matching_etag() {
+    let server = TestServer::start();
+    let url = server.url("/record");
+    let registry = ProtocolHandlerRegistry::default();
+    server.put("/record", b"Foo");
+
+    let (record, version) =
+        fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    assert_eq!(Some("Foo".to_string()), record);
+    assert!(matches!(version, Some(UrlVersion::ETag(_))));
+
+    push_string_to_url_if_version(&url, "Foobar", version.as_ref(), &registry)
+        .expect("Could not push record");
+    assert_eq!(Some(b"Foobar".to_vec()), server.get("/record"));
+
+    let error = push_string_to_url_if_version(&url, "Barfoo", version.as_ref(), &registry)
+        .expect_err("Push should have failed");
+    let conflict = error
+        .downcast_ref::<ConflictError>()
+        .expect("Push should have conflicted");
+    let (_, current) = fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    assert_eq!(version.as_ref(), conflict.expected());
+    assert_eq!(current.as_ref(), conflict.found());
+    assert_eq!(Some(b"Foobar".to_vec()), server.get("/record"));
+}
+
+#[test]
+fn http_can_only_be_created_once_without_etag() {
+    let server = TestServer::start();
+    let url = server.url("/record");
+    let registry = ProtocolHandlerRegistry::default();
+
+    let (record, version) =
+        fetch_string_with_version(&url, &registry).expect("Could not fetch record");
+    assert_eq!(None, record);
+    assert_eq!(None, version);
+
+    push_string_to_url_if_version(&url, "Foo", None, &registry).expect("Could not push record");
+    assert_conflict(push_string_to_url_if_version(&url, "Bar", None, &registry));
+    assert_eq!(Some(b"Foo".to_vec()), server.get("/record"));
+}
@@ -0,0 +1,197 @@
+use super::scp::{build_scp_url, install_fake_ssh};
+use super::*;
+use std::fs::{create_dir_all, write};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn build_tree(root: &Path) {
+    create_dir_all(root.join("sub/deep")).expect("Could not create directories");
+    create_dir_all(root.join(".hidden")).expect("Could not create directories");
+    for file in [
+        "a.toml",
+        "b.json",
+        "sub/c.toml",
+        "sub/deep/d.toml",
+        ".hidden/e.toml",
+    ] {
+        write(root.join(file), "").expect("Could not write file");
+    }
+}
+
+fn walk_relative(root: &Path, options: &WalkOptions) -> Vec<String> {
+    let url = try_build_url_from_path_buf(&root.to_path_buf()).expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    walk_url_container(&url, options, &registry)
+        .expect("Could not walk container")
+        .into_iter()
+        .map(|url| {
+            let path = url.to_file_path().expect("Could not build path");
+            let path = path.strip_prefix(root).expect("Url outside of root");
+            path.to_string_lossy().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn file_container_can_be_walked_recursively() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    build_tree(tmp_dir.path());
+
+    let paths = walk_relative(tmp_dir.path(), &WalkOptions::default());
+    assert_eq!(
+        vec![
+            "a.toml",
+            "b.json",
+            "sub",
+            "sub/c.toml",
+            "sub/deep",
+            "sub/deep/d.toml"
+        ],
+        paths
+    );
+}
+
+#[test]
+fn walk_respects_depth_and_filters() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    build_tree(tmp_dir.path());
+
+    let options = WalkOptions {
+        max_depth: Some(1),
+        ..WalkOptions::default()
+    };
+    assert_eq!(
+        vec!["a.toml", "b.json", "sub"],
+        walk_relative(tmp_dir.path(), &options)
+    );
+
+    let options = WalkOptions {
+        filter: WalkFilter::ContainersOnly,
+        include_hidden: true,
+        ..WalkOptions::default()
+    };
+    assert_eq!(
+        vec![".hidden", "sub", "sub/deep"],
+        walk_relative(tmp_dir.path(), &options)
+    );
+}
+
+#[test]
+fn walk_applies_include_and_exclude_globs() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    build_tree(tmp_dir.path());
+
+    let options = WalkOptions {
+        include: vec!["*.toml".to_string()],
+        exclude: vec!["sub/deep".to_string()],
+        filter: WalkFilter::FilesOnly,
+        ..WalkOptions::default()
+    };
+    assert_eq!(
+        vec!["a.toml", "sub/c.toml"],
+        walk_relative(tmp_dir.path(), &options)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn walk_follows_symlinks_only_when_asked() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    build_tree(tmp_dir.path());
+    std::os::unix::fs::symlink(tmp_dir.path().join("sub"), tmp_dir.path().join("link"))
+        .expect("Could not create symlink");
+    std::os::unix::fs::symlink(tmp_dir.path(), tmp_dir.path().join("sub/loop"))
+        .expect("Could not create symlink");
+
+    let options = WalkOptions {
+        include: vec!["link*".to_string()],
+        ..WalkOptions::default()
+    };
+    assert_eq!(vec!["link"], walk_relative(tmp_dir.path(), &options));
+
+    let options = WalkOptions {
+        include: vec!["link*".to_string()],
+        follow_symlinks: true,
+        ..WalkOptions::default()
+    };
+    assert_eq!(
+        vec![
+            "link",
+            "link/c.toml",
+            "link/deep",
+            "link/deep/d.toml",
+            "link/loop"
+        ],
+        walk_relative(tmp_dir.path(), &options)
+    );
+}
+
+#[test]
+fn archive_container_can_be_walked() {
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.zip"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    let root = Url::parse(&format!("zip+{archive}!/")).expect("Could not build url");
+    create_url_container(&root, &registry).expect("Could not create archive");
+    for member in ["app.toml", "conf/db.toml", "conf/.secret.toml"] {
+        let url = Url::parse(&format!("zip+{archive}!/{member}")).expect("Could not build url");
+        push_string_to_url(&url, "", &registry).expect("Could not push record");
+    }
+
+    let options = WalkOptions {
+        filter: WalkFilter::FilesOnly,
+        ..WalkOptions::default()
+    };
+    let urls = walk_url_container(&root, &options, &registry).expect("Could not walk archive");
+    let members: Vec<&str> = urls
+        .iter()
+        .map(|url| url.as_str().rsplit_once("!/").expect("Not a member").1)
+        .collect();
+    assert_eq!(vec!["app.toml", "conf/db.toml"], members);
+}
+
+#[test]
+fn walk_depth_is_counted_the_same_on_every_protocol() {
+    install_fake_ssh();
+    let tmp_dir: TempDir = TempDir::new().expect("Could not create TempDir");
+    build_tree(&tmp_dir.path().join("tree"));
+    let archive = try_build_url_from_path_buf(&tmp_dir.path().join("bundle.zip"))
+        .expect("Could not build url");
+    let registry = ProtocolHandlerRegistry::default();
+    let archive_root = Url::parse(&format!("zip+{archive}!/")).expect("Could not build url");
+    for member in ["a.toml", "b.json", "sub/c.toml", "sub/deep/d.toml"] {
+        let url = Url::parse(&format!("zip+{archive}!/{member}")).expect("Could not build url");
+        push_string_to_url(&url, "", &registry).expect("Could not push record");
+    }
+    let roots = [
+        try_build_url_from_path_buf(&tmp_dir.path().join("tree")).expect("Could not build url"),
+        build_scp_url(&tmp_dir.path().join("tree")),
+        archive_root,
+    ];
+
+    for (max_depth, expected) in [
+        (0, vec![]),
+        (1, vec!["a.toml", "b.json", "sub"]),
+        (2, vec!["a.toml", "b.json", "sub", "sub/c.toml", "sub/deep"]),
+    ] {
+        let options = WalkOptions {
+            max_depth: Some(max_depth),
+            ..WalkOptions::default()
+        };
+        for root in &roots {
+            let paths: Vec<String> = walk_url_container(root, &options, &registry)
+                .expect("Could not walk container")
+                .iter()
+                .map(|url| {
+                    let path = url.path().strip_prefix(root.path().trim_end_matches('/'));
+                    path.expect("Url outside of root")
+                        .trim_start_matches('/')
+                        .trim_end_matches('/')
+                        .to_string()
+                })
+                .collect();
+            assert_eq!(expected, paths, "Unexpected walk of '{root}' to depth {max_depth}");
+        }
+    }
+}